@@ -7,9 +7,9 @@ use simd_decimal::*;
 const BASE: &[u8; 16] = b"9876543211231_..";
 const BASE_STR: &str = "1234567890123456";
 
-const BASE_INPUT: ParseInput = ParseInput {
-    data: BASE,
-    real_length: 13,
+const BASE_INPUT: ParseInput = match ParseInput::new(BASE, 13) {
+    Some(input) => input,
+    None => panic!("base input is too long"),
 };
 
 // Put us and rust_decimal on the same playing field
@@ -53,6 +53,128 @@ fn run_bench_for<const N: usize, const INT: bool>(c: &mut Criterion) {
     });
 }
 
+fn run_validate_bench_for<const N: usize>(c: &mut Criterion) {
+    let real_input: &[ParseInput; N] = (&MANY[..N]).try_into().unwrap();
+
+    c.bench_function(&format!("Validate batch of {}", N), |b| {
+        let fnc = || {
+            let rval = validate_decimals::<N>(black_box(real_input));
+            black_box(rval);
+            assert_eq!(rval.count_ones() as usize, N);
+        };
+
+        b.iter(fnc);
+    });
+}
+
+// Prices with two decimals summed at a scale of 8, like a column of traded notional
+const SUM_BASE: &[u8; 16] = b"27123.45,0.5____";
+const SUM_INPUT: ParseInput = match ParseInput::new(SUM_BASE, 8) {
+    Some(input) => input,
+    None => panic!("sum input is too long"),
+};
+const SUM_SCALE: u8 = 8;
+
+fn run_sum_bench_for<const N: usize>(c: &mut Criterion) {
+    let real_input = [SUM_INPUT; N];
+
+    c.bench_function(&format!("Sum batch of {}", N), |b| {
+        let fnc = || {
+            let mut total = 0;
+            let rval = sum_decimals::<N>(black_box(&real_input), SUM_SCALE, &mut total);
+            black_box(total);
+            assert!(rval);
+        };
+
+        b.iter(fnc);
+    });
+
+    c.bench_function(&format!("Parse then sum batch of {}", N), |b| {
+        let fnc = || {
+            let mut outputs = [ParseOutput::default(); N];
+            let rval = parse_decimals::<N, false>(black_box(&real_input), &mut outputs);
+            let total: i128 = outputs
+                .iter()
+                .map(|o| o.mantissa as i128 * 10i128.pow((SUM_SCALE - o.exponent) as u32))
+                .sum();
+            black_box(total);
+            assert!(rval);
+        };
+
+        b.iter(fnc);
+    });
+}
+
+// A quantity short enough for the u64 path. It beats the vector parser at every batch size
+// with the default target, but with target-cpu=native it only ties at 1 and 16 and loses at 4 and 8
+const SHORT_BASE: &[u8; 16] = b"1234.5,27123.45_";
+const SHORT_INPUT: ParseInput = match ParseInput::new(SHORT_BASE, 6) {
+    Some(input) => input,
+    None => panic!("short input is too long"),
+};
+
+fn run_short_bench_for<const N: usize>(c: &mut Criterion) {
+    let real_input = [SHORT_INPUT; N];
+    let mut outputs = [ParseOutput::default(); N];
+
+    c.bench_function(&format!("Short parse batch of {}", N), |b| {
+        let fnc = || {
+            let rval =
+                parse_short_decimals::<N, false>(black_box(&real_input), black_box(&mut outputs));
+            black_box(&outputs);
+            black_box(rval);
+            assert!(rval);
+        };
+
+        b.iter(fnc);
+    });
+
+    c.bench_function(&format!("Raw parse short batch of {}", N), |b| {
+        let fnc = || {
+            let rval = parse_decimals::<N, false>(black_box(&real_input), black_box(&mut outputs));
+            black_box(&outputs);
+            black_box(rval);
+            assert!(rval);
+        };
+
+        b.iter(fnc);
+    });
+}
+
+// Something like a size, small enough for four of them to share a vector
+const SMALL_BASE: &[u8; 16] = b"1234,5678,90____";
+const SMALL_INPUT: ParseInput = match ParseInput::new(SMALL_BASE, 4) {
+    Some(input) => input,
+    None => panic!("small input is too long"),
+};
+
+fn run_small_integer_bench(c: &mut Criterion) {
+    let real_input = [SMALL_INPUT; 4];
+    let mut outputs = [0; 4];
+
+    c.bench_function("Small integers batch of 4", |b| {
+        let fnc = || {
+            let rval = parse_small_integers::<4>(black_box(&real_input), black_box(&mut outputs));
+            black_box(&outputs);
+            black_box(rval);
+            assert!(rval);
+        };
+
+        b.iter(fnc);
+    });
+
+    c.bench_function("Parse u32s small batch of 4", |b| {
+        let fnc = || {
+            let rval = parse_u32s::<4>(black_box(&real_input), black_box(&mut outputs));
+            black_box(&outputs);
+            black_box(rval);
+            assert!(rval);
+        };
+
+        b.iter(fnc);
+    });
+}
+
 fn run_decimal_bench_for<const N: usize>(c: &mut Criterion) {
     c.bench_function(&format!("Decimal parse batch of {}", N), |b| {
         let fnc = || {
@@ -94,6 +216,54 @@ fn run_float_bench_16(c: &mut Criterion) {
     run_bench_for::<16, false>(c);
 }
 
+fn run_validate_bench_1(c: &mut Criterion) {
+    run_validate_bench_for::<1>(c);
+}
+
+fn run_validate_bench_2(c: &mut Criterion) {
+    run_validate_bench_for::<2>(c);
+}
+
+fn run_validate_bench_4(c: &mut Criterion) {
+    run_validate_bench_for::<4>(c);
+}
+
+fn run_validate_bench_8(c: &mut Criterion) {
+    run_validate_bench_for::<8>(c);
+}
+
+fn run_validate_bench_16(c: &mut Criterion) {
+    run_validate_bench_for::<16>(c);
+}
+
+fn run_sum_bench_4(c: &mut Criterion) {
+    run_sum_bench_for::<4>(c);
+}
+
+fn run_sum_bench_8(c: &mut Criterion) {
+    run_sum_bench_for::<8>(c);
+}
+
+fn run_sum_bench_16(c: &mut Criterion) {
+    run_sum_bench_for::<16>(c);
+}
+
+fn run_short_bench_1(c: &mut Criterion) {
+    run_short_bench_for::<1>(c);
+}
+
+fn run_short_bench_4(c: &mut Criterion) {
+    run_short_bench_for::<4>(c);
+}
+
+fn run_short_bench_8(c: &mut Criterion) {
+    run_short_bench_for::<8>(c);
+}
+
+fn run_short_bench_16(c: &mut Criterion) {
+    run_short_bench_for::<16>(c);
+}
+
 fn run_dec_bench_1(c: &mut Criterion) {
     run_decimal_bench_for::<1>(c);
 }
@@ -160,6 +330,32 @@ criterion_group!(
     run_integer_bench_16,
 );
 
+// Compare these against the float batches of the same size
+criterion_group!(
+    validate_benches,
+    run_validate_bench_1,
+    run_validate_bench_2,
+    run_validate_bench_4,
+    run_validate_bench_8,
+    run_validate_bench_16,
+);
+
+criterion_group!(
+    sum_benches,
+    run_sum_bench_4,
+    run_sum_bench_8,
+    run_sum_bench_16,
+);
+
+criterion_group!(
+    short_benches,
+    run_short_bench_1,
+    run_short_bench_4,
+    run_short_bench_8,
+    run_short_bench_16,
+    run_small_integer_bench,
+);
+
 criterion_group!(
     decimal_parse_benches,
     run_dec_bench_1,
@@ -168,4 +364,10 @@ criterion_group!(
     run_dec_bench_8,
     run_dec_bench_16,
 );
-criterion_main!(raw_parse_benches, decimal_parse_benches);
+criterion_main!(
+    raw_parse_benches,
+    validate_benches,
+    sum_benches,
+    short_benches,
+    decimal_parse_benches
+);
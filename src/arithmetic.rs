@@ -0,0 +1,348 @@
+//! Checked arithmetic on [`ParseOutput`], which stays in the mantissa and exponent
+//! representation. Every operation returns None instead of overflowing
+
+use crate::ParseOutput;
+
+/// What to do with the digits that get dropped when reducing the number of decimals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Drop them, rounding towards zero
+    Truncate,
+    /// Round to the nearest, with ties going away from zero
+    HalfUp,
+    /// Round to the nearest, with ties going to the even neighbour
+    HalfEven,
+    /// Fail unless every dropped digit is zero
+    Reject,
+}
+
+impl ParseOutput {
+    /// Adds the two, with the result having the larger of the two exponents
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// use simd_decimal::ParseOutput;
+    ///
+    /// let a = ParseOutput { mantissa: 15, exponent: 1 };
+    /// let b = ParseOutput { mantissa: 125, exponent: 2 };
+    /// assert_eq!(a.checked_add(b), Some(ParseOutput { mantissa: 275, exponent: 2 }));
+    /// ```
+    #[inline]
+    pub fn checked_add(self, other: ParseOutput) -> Option<ParseOutput> {
+        let (a, b, exponent) = align(self, other)?;
+        Some(ParseOutput {
+            mantissa: a.checked_add(b)?,
+            exponent,
+        })
+    }
+
+    /// Subtracts other, with the result having the larger of the two exponents.
+    /// Outputs are unsigned, so this also fails if other is larger
+    #[inline]
+    pub fn checked_sub(self, other: ParseOutput) -> Option<ParseOutput> {
+        let (a, b, exponent) = align(self, other)?;
+        Some(ParseOutput {
+            mantissa: a.checked_sub(b)?,
+            exponent,
+        })
+    }
+
+    /// Multiplies the two, adding the exponents. This is exact, so the result
+    /// may need to be rescaled to get back to a sensible number of decimals
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// use simd_decimal::*;
+    ///
+    /// let price = ParseOutput { mantissa: 2712345, exponent: 2 };
+    /// let qty = ParseOutput { mantissa: 125, exponent: 3 };
+    /// let notional = price.checked_mul(qty).unwrap();
+    ///
+    /// assert_eq!(notional, ParseOutput { mantissa: 339043125, exponent: 5 });
+    /// assert_eq!(
+    ///     notional.rescale(2, RoundingMode::HalfEven),
+    ///     Some(ParseOutput { mantissa: 339043, exponent: 2 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn checked_mul(self, other: ParseOutput) -> Option<ParseOutput> {
+        Some(ParseOutput {
+            mantissa: self.mantissa.checked_mul(other.mantissa)?,
+            exponent: self.exponent.checked_add(other.exponent)?,
+        })
+    }
+
+    /// Changes the exponent to new_exponent. Adding decimals is exact,
+    /// and removing them rounds according to mode
+    #[inline]
+    pub fn rescale(self, new_exponent: u8, mode: RoundingMode) -> Option<ParseOutput> {
+        let mantissa = if new_exponent >= self.exponent {
+            scale_up(self.mantissa, new_exponent - self.exponent)?
+        } else {
+            scale_down(self.mantissa, self.exponent - new_exponent, mode)?
+        };
+        Some(ParseOutput {
+            mantissa,
+            exponent: new_exponent,
+        })
+    }
+}
+
+/// Returns a mask with bit i set if output i is not a multiple of tick.
+/// This is for checking prices against a tick size, or quantities against a lot size.
+/// Zero is on every tick, and nothing else is a multiple of a zero tick.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let prices = [
+///     ParseOutput { mantissa: 10015, exponent: 2 },
+///     ParseOutput { mantissa: 1001, exponent: 1 },
+///     ParseOutput { mantissa: 100125, exponent: 3 },
+/// ];
+/// let tick = ParseOutput { mantissa: 5, exponent: 2 };
+///
+/// assert_eq!(validate_ticks(&prices, tick), 0b100);
+/// ```
+#[inline]
+pub fn validate_ticks<const N: usize>(outputs: &[ParseOutput; N], tick: ParseOutput) -> u32 {
+    assert!(N <= 32, "validate_ticks returns a u32 mask");
+    let mut off_tick = 0;
+    for (i, output) in outputs.iter().enumerate() {
+        off_tick |= (!is_on_tick(*output, tick) as u32) << i;
+    }
+    off_tick
+}
+
+#[inline]
+fn is_on_tick(value: ParseOutput, tick: ParseOutput) -> bool {
+    if value.mantissa == 0 {
+        return true;
+    }
+    if tick.mantissa == 0 {
+        return false;
+    }
+
+    let tick_mantissa = tick.mantissa as u128;
+    if value.exponent <= tick.exponent {
+        // The value has to be a multiple of the tick once it's scaled up to the tick's decimals.
+        // Scaling up the remainder a digit at a time means this can never overflow
+        let mut remainder = value.mantissa as u128 % tick_mantissa;
+        for _ in value.exponent..tick.exponent {
+            if remainder == 0 {
+                break;
+            }
+            remainder = remainder * 10 % tick_mantissa;
+        }
+        remainder == 0
+    } else {
+        // A tick too big to scale up is bigger than any mantissa
+        let scaled_tick = 10u128
+            .checked_pow((value.exponent - tick.exponent) as u32)
+            .and_then(|power| power.checked_mul(tick_mantissa));
+        let remainder = scaled_tick.map_or(1, |scaled_tick| value.mantissa as u128 % scaled_tick);
+        remainder == 0
+    }
+}
+
+/// Scales both mantissas up to the larger exponent
+#[inline]
+fn align(a: ParseOutput, b: ParseOutput) -> Option<(u64, u64, u8)> {
+    let exponent = a.exponent.max(b.exponent);
+    Some((
+        scale_up(a.mantissa, exponent - a.exponent)?,
+        scale_up(b.mantissa, exponent - b.exponent)?,
+        exponent,
+    ))
+}
+
+#[inline]
+fn scale_up(mantissa: u64, by: u8) -> Option<u64> {
+    if mantissa == 0 {
+        return Some(0);
+    }
+    mantissa.checked_mul(10u64.checked_pow(by as u32)?)
+}
+
+/// Divides by 10^by and rounds the result
+#[inline]
+fn scale_down(mantissa: u64, by: u8, mode: RoundingMode) -> Option<u64> {
+    // Past 10^38 the divisor doesn't fit, but every u64 rounds to zero well before that
+    let divisor = 10u128.checked_pow(by as u32).unwrap_or(u128::MAX);
+    let quotient = (mantissa as u128 / divisor) as u64;
+    let remainder = mantissa as u128 % divisor;
+
+    // Comparing the remainder against half of the divisor, without halving an odd divisor
+    let twice_remainder = remainder * 2;
+    let round_up = match mode {
+        RoundingMode::Truncate => false,
+        RoundingMode::HalfUp => twice_remainder >= divisor,
+        RoundingMode::HalfEven => {
+            twice_remainder > divisor || (twice_remainder == divisor && quotient & 1 == 1)
+        }
+        RoundingMode::Reject if remainder != 0 => return None,
+        RoundingMode::Reject => false,
+    };
+
+    // The quotient is at most u64::MAX / 10, so this can't overflow
+    Some(quotient + round_up as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(mantissa: u64, exponent: u8) -> ParseOutput {
+        ParseOutput { mantissa, exponent }
+    }
+
+    #[test]
+    fn test_add_sub() {
+        assert_eq!(output(1, 0).checked_add(output(5, 1)), Some(output(15, 1)));
+        assert_eq!(
+            output(0, 3).checked_add(output(7, 0)),
+            Some(output(7000, 3))
+        );
+        assert_eq!(output(1, 0).checked_add(output(1, 20)), None);
+        assert_eq!(output(u64::MAX, 0).checked_add(output(1, 0)), None);
+
+        assert_eq!(
+            output(2, 0).checked_sub(output(25, 2)),
+            Some(output(175, 2))
+        );
+        assert_eq!(output(25, 2).checked_sub(output(2, 0)), None);
+        assert_eq!(output(5, 1).checked_sub(output(50, 2)), Some(output(0, 2)));
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(output(15, 1).checked_mul(output(2, 0)), Some(output(30, 1)));
+        assert_eq!(output(1 << 32, 0).checked_mul(output(1 << 32, 0)), None);
+        assert_eq!(output(1, 200).checked_mul(output(1, 100)), None);
+    }
+
+    #[test]
+    fn test_rescale() {
+        assert_eq!(
+            output(15, 1).rescale(4, RoundingMode::Reject),
+            Some(output(15000, 4))
+        );
+        assert_eq!(output(u64::MAX, 0).rescale(1, RoundingMode::Truncate), None);
+        assert_eq!(
+            output(0, 0).rescale(255, RoundingMode::Reject),
+            Some(output(0, 255))
+        );
+
+        assert_eq!(
+            output(123456789, 6).rescale(4, RoundingMode::Truncate),
+            Some(output(1234567, 4))
+        );
+        assert_eq!(
+            output(123456789, 6).rescale(4, RoundingMode::HalfUp),
+            Some(output(1234568, 4))
+        );
+        assert_eq!(output(123456789, 6).rescale(4, RoundingMode::Reject), None);
+
+        // Far more decimals removed than a u64 has digits
+        assert_eq!(
+            output(u64::MAX, 255).rescale(0, RoundingMode::HalfUp),
+            Some(output(0, 0))
+        );
+        assert_eq!(
+            output(u64::MAX, 20).rescale(0, RoundingMode::HalfUp),
+            Some(output(0, 0))
+        );
+        assert_eq!(
+            output(u64::MAX, 19).rescale(0, RoundingMode::HalfUp),
+            Some(output(2, 0))
+        );
+    }
+
+    /// Rounds by picking whichever neighbour is closer, which is a different route
+    /// to the same answer as scale_down
+    fn reference_round(mantissa: u64, by: u32, mode: RoundingMode) -> Option<u128> {
+        let (mantissa, divisor) = (mantissa as u128, 10u128.pow(by));
+        let down = mantissa / divisor;
+        let up = down + 1;
+        let below = mantissa - down * divisor;
+        let above = up * divisor - mantissa;
+
+        match mode {
+            _ if below == 0 => Some(down),
+            RoundingMode::Reject => None,
+            RoundingMode::Truncate => Some(down),
+            _ if below < above => Some(down),
+            _ if below > above => Some(up),
+            RoundingMode::HalfUp => Some(up),
+            RoundingMode::HalfEven => Some(if down & 1 == 0 { down } else { up }),
+        }
+    }
+
+    #[test]
+    fn test_rounding_exhaustive() {
+        let modes = [
+            RoundingMode::Truncate,
+            RoundingMode::HalfUp,
+            RoundingMode::HalfEven,
+            RoundingMode::Reject,
+        ];
+
+        // Every mantissa up to four digits, dropping every number of them
+        for mantissa in 0..10_000 {
+            for by in 1..=5 {
+                for mode in modes {
+                    let rescaled = output(mantissa, by).rescale(0, mode);
+                    assert_eq!(
+                        rescaled.map(|o| o.mantissa as u128),
+                        reference_round(mantissa, by as u32, mode),
+                        "{} {} {:?}",
+                        mantissa,
+                        by,
+                        mode
+                    );
+                }
+            }
+        }
+
+        // Ties and carries right at the top of the range
+        for mantissa in u64::MAX - 10_000..=u64::MAX {
+            for by in [1, 2, 19] {
+                for mode in modes {
+                    let rescaled = output(mantissa, by).rescale(0, mode);
+                    assert_eq!(
+                        rescaled.map(|o| o.mantissa as u128),
+                        reference_round(mantissa, by as u32, mode)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_ticks() {
+        let tick = output(5, 2);
+        let outputs = [
+            output(10015, 2),
+            output(1001, 1),
+            output(100125, 3),
+            output(100150, 3),
+            output(0, 9),
+            output(7, 0),
+            output(u64::MAX, 200),
+            output(1, 2),
+        ];
+        assert_eq!(validate_ticks(&outputs, tick), 0b11000100);
+
+        // A tick with fewer decimals than the values
+        let lots = [output(300, 2), output(250, 2), output(3, 0), output(4, 0)];
+        assert_eq!(validate_ticks(&lots, output(3, 0)), 0b1010);
+        assert_eq!(validate_ticks(&lots, output(1, 30)), 0);
+        assert_eq!(validate_ticks(&lots, output(0, 0)), 0b1111);
+        assert_eq!(validate_ticks(&[output(1, 255)], output(1, 0)), 0b1);
+        assert_eq!(validate_ticks(&[output(0, 255)], output(0, 0)), 0);
+    }
+}
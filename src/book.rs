@@ -0,0 +1,293 @@
+//! Parses order book levels out of JSON arrays of `[price, qty]` pairs,
+//! like the bids and asks of a depth snapshot: `[["27123.45","0.5"],["27123.40","1.25"]]`.
+//!
+//! The brackets and commas are found with the same chunked scanning as [`crate::scanner`],
+//! the values are read with [`JsonNumbers`], and the prices and quantities
+//! are parsed together in batches of N.
+
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::json::{JsonError, JsonNumbers};
+use crate::scanner::{break_on_error, for_each_chunk, BatchParser, ScanError};
+use crate::{ParseInput, ParseOutput};
+
+/// Error from parsing order book levels, with the index of the offending level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelError {
+    /// The level isn't a pair of values, or the array around it is broken
+    Malformed { level: usize },
+    /// The price or quantity is longer than 16 bytes
+    TooLong { level: usize },
+    /// The price or quantity isn't a valid decimal
+    InvalidNumber { level: usize },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Malformed { level } => write!(f, "level {} is malformed", level),
+            LevelError::TooLong { level } => write!(f, "level {} has a value too long", level),
+            LevelError::InvalidNumber { level } => {
+                write!(f, "level {} has a value that is not a decimal", level)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+/// Receives each parsed (price, qty) level in order
+pub trait LevelSink {
+    fn level(&mut self, price: ParseOutput, qty: ParseOutput);
+}
+
+impl<F: FnMut(ParseOutput, ParseOutput)> LevelSink for F {
+    #[inline]
+    fn level(&mut self, price: ParseOutput, qty: ParseOutput) {
+        self(price, qty)
+    }
+}
+
+/// Parses an array of levels and returns them in order.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::book::parse_levels;
+///
+/// let levels = parse_levels::<16>(br#"[["27123.45","0.5"], ["27123.4", "1.25"]]"#).unwrap();
+///
+/// assert_eq!(levels.len(), 2);
+/// assert_eq!((levels[1].0.mantissa, levels[1].1.mantissa), (271234, 125));
+/// ```
+pub fn parse_levels<const N: usize>(
+    buffer: &[u8],
+) -> Result<Vec<(ParseOutput, ParseOutput)>, LevelError> {
+    let mut levels = Vec::new();
+    parse_levels_into::<N, _>(buffer, &mut |price, qty| levels.push((price, qty)))?;
+    Ok(levels)
+}
+
+/// Parses an array of levels, and sends each one to sink as soon as its batch is parsed.
+/// Prices and quantities go through the same batches, so N / 2 levels are parsed at a time
+/// and N should be 8 or 16. Returns the number of levels.
+///
+/// Levels before the one that failed may have already been sent to the sink
+pub fn parse_levels_into<const N: usize, S: LevelSink>(
+    buffer: &[u8],
+    sink: &mut S,
+) -> Result<usize, LevelError> {
+    let numbers = JsonNumbers::new(buffer);
+    let mut batch = BatchParser::<N>::new();
+    let mut parsed = Vec::with_capacity(N);
+
+    let mut depth = 0;
+    let mut level = 0;
+    let mut values = 0;
+    let mut value_start = 0;
+    // Between the levels, whether the next one is due rather than a comma or the closing bracket.
+    // A closing bracket is only fine when it is due before the first level, in an empty array
+    let mut expect_level = true;
+    // Where the bytes since the last structural character start
+    let mut gap_start = 0;
+    let mut finished = false;
+
+    let mut on_structural = |byte: u8, position: usize| -> Result<(), LevelError> {
+        let malformed = Err(LevelError::Malformed { level });
+        if finished {
+            return malformed;
+        }
+
+        // Outside of a level only whitespace can be between the brackets and commas.
+        // Inside of one, read_value checks around the values instead
+        let gap = &buffer[gap_start..position];
+        gap_start = position + 1;
+        if depth < 2 && !gap.iter().all(u8::is_ascii_whitespace) {
+            return malformed;
+        }
+
+        match (byte, depth) {
+            (b'[', 0) => depth = 1,
+            (b'[', 1) if expect_level => {
+                depth = 2;
+                values = 0;
+                value_start = position + 1;
+            }
+            (b',', 1) if !expect_level => expect_level = true,
+            (b',', 2) | (b']', 2) => {
+                let input = read_value(&numbers, buffer, value_start, position, level)?;
+                if values == 2 {
+                    return malformed;
+                }
+                values += 1;
+                value_start = position + 1;
+
+                batch.push(input, &mut parsed).map_err(level_error)?;
+                drain_levels(&mut parsed, sink);
+
+                if byte == b']' {
+                    if values != 2 {
+                        return malformed;
+                    }
+                    depth = 1;
+                    level += 1;
+                    expect_level = false;
+                }
+            }
+            (b']', 1) if !expect_level || level == 0 => finished = true,
+            _ => return malformed,
+        }
+        Ok(())
+    };
+
+    let scanned = for_each_chunk(
+        buffer,
+        [b'[', b']', b','],
+        |chunk_start, [opens, closes, commas]| {
+            let mut structural = opens | closes | commas;
+            while structural != 0 {
+                let position = chunk_start + structural.trailing_zeros() as usize;
+                break_on_error(on_structural(buffer[position], position))?;
+                structural &= structural - 1;
+            }
+            ControlFlow::Continue(())
+        },
+    );
+    if let ControlFlow::Break(error) = scanned {
+        return Err(error);
+    }
+
+    if !finished || !buffer[gap_start..].iter().all(u8::is_ascii_whitespace) {
+        return Err(LevelError::Malformed { level });
+    }
+
+    batch.finish(&mut parsed).map_err(level_error)?;
+    drain_levels(&mut parsed, sink);
+
+    Ok(level)
+}
+
+/// Reads the value between two structural characters, which may have whitespace around it
+fn read_value<'a>(
+    numbers: &'a JsonNumbers,
+    buffer: &[u8],
+    start: usize,
+    end: usize,
+    level: usize,
+) -> Result<ParseInput<'a>, LevelError> {
+    let is_space = |b: &u8| b.is_ascii_whitespace();
+    let offset = start
+        + buffer[start..end]
+            .iter()
+            .take_while(|b| is_space(b))
+            .count();
+    if offset == end {
+        return Err(LevelError::Malformed { level });
+    }
+
+    let input = numbers.input_at(offset).map_err(|e| match e {
+        JsonError::TooLong { .. } => LevelError::TooLong { level },
+        _ => LevelError::Malformed { level },
+    })?;
+
+    // Only whitespace is allowed between the value and the next comma or bracket
+    let quotes = if buffer[offset] == b'"' { 2 } else { 0 };
+    let value_end = offset + input.real_length.get() + quotes;
+    if value_end > end || !buffer[value_end..end].iter().all(is_space) {
+        return Err(LevelError::Malformed { level });
+    }
+
+    Ok(input)
+}
+
+/// Prices and quantities are pushed alternately, so batch errors are at index 2 * level
+fn level_error(error: ScanError) -> LevelError {
+    match error {
+        ScanError::InvalidField { field } | ScanError::FieldTooLong { field } => {
+            LevelError::InvalidNumber { level: field / 2 }
+        }
+    }
+}
+
+/// Sends every complete pair in parsed to the sink
+#[inline]
+fn drain_levels<S: LevelSink>(parsed: &mut Vec<ParseOutput>, sink: &mut S) {
+    let complete = parsed.len() & !1;
+    for pair in parsed[..complete].chunks_exact(2) {
+        sink.level(pair[0], pair[1]);
+    }
+    parsed.drain(..complete);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mantissas(buffer: &[u8]) -> Result<Vec<(u64, u64)>, LevelError> {
+        let levels = parse_levels::<8>(buffer)?;
+        Ok(levels
+            .iter()
+            .map(|(price, qty)| (price.mantissa, qty.mantissa))
+            .collect())
+    }
+
+    #[test]
+    fn test_many_levels() {
+        let expected: Vec<(u64, u64)> = (0..23).map(|i| (27000 + i, i * 3)).collect();
+        let buffer = format!(
+            "[{}]",
+            expected
+                .iter()
+                .map(|(price, qty)| format!("[\"{}\", {}]", price, qty))
+                .collect::<Vec<_>>()
+                .join(",\n ")
+        );
+
+        assert_eq!(mantissas(buffer.as_bytes()).unwrap(), expected);
+
+        let mut streamed = 0;
+        let count = parse_levels_into::<16, _>(buffer.as_bytes(), &mut |_, _| streamed += 1);
+        assert_eq!(count, Ok(23));
+        assert_eq!(streamed, 23);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(mantissas(b"[]").unwrap(), []);
+        assert_eq!(mantissas(b" [ ] ").unwrap(), []);
+    }
+
+    #[test]
+    fn test_errors() {
+        let malformed = |level| Err(LevelError::Malformed { level });
+        assert_eq!(mantissas(br#"[["1","2"],["3"]]"#), malformed(1));
+        assert_eq!(mantissas(br#"[["1","2","3"]]"#), malformed(0));
+        assert_eq!(mantissas(br#"[["1" "2"]]"#), malformed(0));
+        assert_eq!(mantissas(br#"[["1","2"]"#), malformed(1));
+        assert_eq!(mantissas(br#"[["1",["2"]]]"#), malformed(0));
+
+        // Levels have to be separated by exactly one comma
+        assert_eq!(mantissas(br#"[["1","2"]["3","4"]]"#), malformed(1));
+        assert_eq!(mantissas(br#"[,["1","2"]]"#), malformed(0));
+        assert_eq!(mantissas(br#"[["1","2"],]"#), malformed(1));
+        assert_eq!(mantissas(br#"[["1","2"],,["3","4"]]"#), malformed(1));
+
+        // Junk outside of the levels, rather than inside of a value
+        assert_eq!(mantissas(br#"x[["1","2"]]"#), malformed(0));
+        assert_eq!(mantissas(br#"[x["1","2"]]"#), malformed(0));
+        assert_eq!(mantissas(br#"[["1","2"]x,["3","4"]]"#), malformed(1));
+        assert_eq!(mantissas(br#"[["1","2"],x["3","4"]]"#), malformed(1));
+        assert_eq!(mantissas(br#"[["1","2"],["3","4"]]garbage"#), malformed(2));
+        assert!(mantissas(b" [ [\"1\",\"2\"] ,\n[\"3\",\"4\"] ]\r\n").is_ok());
+
+        assert_eq!(
+            mantissas(br#"[["1","2"],["3","4"],["5","6"],["7","x"]]"#),
+            Err(LevelError::InvalidNumber { level: 3 })
+        );
+        assert_eq!(
+            mantissas(br#"[["1","12345678901234567"]]"#),
+            Err(LevelError::TooLong { level: 0 })
+        );
+    }
+}
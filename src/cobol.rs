@@ -0,0 +1,103 @@
+//! Scalar helpers for the signs of mainframe decimal encodings.
+//! Zoned decimals can be in ASCII or EBCDIC, and either is turned into plain ASCII digits
+
+use crate::{ParseOutput, SignedParseOutput};
+
+/// Packed decimals can be at most 8 bytes, as 15 digits and a sign are all that fit in a vector
+pub(crate) const MAX_PACKED_BYTES: usize = 8;
+
+// Marks an overpunched digit as negative in OVERPUNCH
+const NEGATIVE: u8 = 0x80;
+
+// Maps the last byte of a zoned decimal to its plain ascii digit, with NEGATIVE set if the sign
+// is negative. Plain digits are unsigned, and anything that isn't a digit maps to 0xff
+static OVERPUNCH: [u8; 256] = generate_overpunch();
+
+const fn generate_overpunch() -> [u8; 256] {
+    let mut table = [u8::MAX; 256];
+    let mut digit = 0;
+    while digit < 10 {
+        table[(b'0' + digit) as usize] = b'0' + digit;
+        if digit > 0 {
+            table[(b'A' + digit - 1) as usize] = b'0' + digit;
+            table[(b'J' + digit - 1) as usize] = (b'0' + digit) | NEGATIVE;
+        }
+        digit += 1;
+    }
+    table[b'{' as usize] = b'0';
+    table[b'}' as usize] = b'0' | NEGATIVE;
+    table
+}
+
+// EBCDIC digits are 0xF0 to 0xF9, so this turns them into ASCII digits and nothing else into one
+const EBCDIC_ZONE: u8 = 0xF0 ^ b'0';
+
+// Like OVERPUNCH, but for EBCDIC where the zone nibble of the last byte is the sign.
+// 0xC is positive, 0xD is negative and 0xF is unsigned
+static EBCDIC_OVERPUNCH: [u8; 256] = generate_ebcdic_overpunch();
+
+const fn generate_ebcdic_overpunch() -> [u8; 256] {
+    let mut table = [u8::MAX; 256];
+    let mut digit = 0;
+    while digit < 10 {
+        table[(0xF0 + digit) as usize] = b'0' + digit;
+        table[(0xC0 + digit) as usize] = b'0' + digit;
+        table[(0xD0 + digit) as usize] = (b'0' + digit) | NEGATIVE;
+        digit += 1;
+    }
+    table
+}
+
+/// Decodes the sign nibble at the end of a packed decimal.
+/// Returns whether it is negative, or None if it isn't a sign.
+/// C and D are the preferred signs, F is unsigned, and A, B and E are alternates
+#[inline(always)]
+pub(crate) fn packed_sign_is_negative(nibble: u8) -> Option<bool> {
+    match nibble {
+        0xA | 0xC | 0xE | 0xF => Some(false),
+        0xB | 0xD => Some(true),
+        _ => None,
+    }
+}
+
+/// Replaces the overpunched last byte of a zoned decimal with the digit it holds,
+/// and for EBCDIC turns the other digits into ASCII ones too.
+/// Returns whether it is negative, or None if there is no last byte.
+/// A byte which isn't a digit is replaced with something that fails the digit check
+#[inline(always)]
+pub(crate) fn remove_overpunch<const EBCDIC: bool>(
+    data: &mut [u8; 16],
+    length: usize,
+) -> Option<bool> {
+    let last_idx = length.checked_sub(1)?;
+    let last = *data.get(last_idx)?;
+
+    let unpunched = if EBCDIC {
+        for byte in data.iter_mut() {
+            *byte ^= EBCDIC_ZONE;
+        }
+        EBCDIC_OVERPUNCH[last as usize]
+    } else {
+        OVERPUNCH[last as usize]
+    };
+    data[last_idx] = unpunched & !NEGATIVE;
+    Some(unpunched != u8::MAX && unpunched & NEGATIVE != 0)
+}
+
+/// Gives each mantissa its sign, and every output the same exponent.
+/// The mantissas must have at most 16 digits, so that negating them can't overflow
+#[inline(always)]
+pub(crate) fn apply_signs<const N: usize>(
+    unsigned: &[ParseOutput; N],
+    negative: &[bool; N],
+    exponent: u8,
+    outputs: &mut [SignedParseOutput; N],
+) {
+    for i in 0..N {
+        let mantissa = unsigned[i].mantissa as i64;
+        outputs[i] = SignedParseOutput {
+            mantissa: if negative[i] { -mantissa } else { mantissa },
+            exponent,
+        };
+    }
+}
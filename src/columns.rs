@@ -0,0 +1,57 @@
+//! Where the parsing stages write their results, so the same kernels can fill
+//! either an array of [`ParseOutput`] or separate mantissa and exponent arrays.
+
+use crate::ParseOutput;
+
+/// Destination for the mantissas and exponents of a batch.
+/// Stages hand over every lane at once, which lets the split layout
+/// write each column with one store
+pub(crate) trait OutputColumns<const N: usize> {
+    fn set_exponents(&mut self, exponents: [u8; N]);
+    fn set_mantissas(&mut self, mantissas: [u64; N]);
+}
+
+impl<const N: usize> OutputColumns<N> for [ParseOutput; N] {
+    #[inline(always)]
+    fn set_exponents(&mut self, exponents: [u8; N]) {
+        for i in 0..N {
+            self[i].exponent = exponents[i];
+        }
+    }
+
+    #[inline(always)]
+    fn set_mantissas(&mut self, mantissas: [u64; N]) {
+        for i in 0..N {
+            self[i].mantissa = mantissas[i];
+        }
+    }
+}
+
+/// Mantissas and exponents in their own arrays
+pub(crate) struct SplitOutputs<'a, const N: usize> {
+    pub mantissas: &'a mut [u64; N],
+    pub exponents: &'a mut [u8; N],
+}
+
+impl<const N: usize> OutputColumns<N> for SplitOutputs<'_, N> {
+    #[inline(always)]
+    fn set_exponents(&mut self, exponents: [u8; N]) {
+        *self.exponents = exponents;
+    }
+
+    #[inline(always)]
+    fn set_mantissas(&mut self, mantissas: [u64; N]) {
+        *self.mantissas = mantissas;
+    }
+}
+
+/// Plain integers, which have no exponents to write
+impl<const N: usize> OutputColumns<N> for [u64; N] {
+    #[inline(always)]
+    fn set_exponents(&mut self, _: [u8; N]) {}
+
+    #[inline(always)]
+    fn set_mantissas(&mut self, mantissas: [u64; N]) {
+        *self = mantissas;
+    }
+}
@@ -0,0 +1,323 @@
+//! Extracts decimal columns out of CSV buffers.
+//!
+//! This uses the same chunked scanning as [`crate::scanner`], except quotes are tracked
+//! so that delimiters and newlines inside of quoted fields are ignored.
+//! Each requested column gets its own batch of inputs, which is parsed N at a time.
+
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::scanner::{break_on_error, for_each_chunk, BatchParser, PaddedTail, ScanError};
+use crate::{ParseInput, ParseOutput};
+
+/// Error from extracting CSV columns, with the zero-based row and column of the field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvError {
+    pub row: usize,
+    pub column: usize,
+    pub kind: CsvErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvErrorKind {
+    /// The field is longer than 16 bytes after removing quotes
+    FieldTooLong,
+    /// The field isn't a valid decimal
+    InvalidField,
+    /// The row doesn't have this column
+    MissingColumn,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            CsvErrorKind::FieldTooLong => "is too long",
+            CsvErrorKind::InvalidField => "is not a decimal",
+            CsvErrorKind::MissingColumn => "is missing",
+        };
+        write!(f, "row {} column {} {}", self.row, self.column, problem)
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// Parses the requested columns of every row, and returns the values of each column
+/// in the same order as columns. The columns must be distinct.
+///
+/// Fields may be wrapped in quote, and rows may end in either LF or CRLF.
+/// A newline at the very end of the buffer doesn't start a new row, but any other empty row
+/// is missing all of its columns. Empty fields parse as zero like they do in [`crate::parse_decimals`].
+/// There's no special handling of a header, so skip past it before calling this.
+///
+/// The error isn't always in the first bad row. Values are only checked once N rows of
+/// their column are batched up, so a missing column, or a bad value in a column whose batch
+/// is parsed first, can be reported before an invalid value in an earlier row.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::csv::extract_columns;
+///
+/// let csv = b"BTCUSDT,\"27123.45\",0.5\r\nBTCUSDT,\"27124\",1.25\r\n";
+/// let columns = extract_columns::<8>(csv, b',', b'"', &[1, 2]).unwrap();
+///
+/// let prices: Vec<u64> = columns[0].iter().map(|o| o.mantissa).collect();
+/// let sizes: Vec<u64> = columns[1].iter().map(|o| o.mantissa).collect();
+/// assert_eq!(prices, [2712345, 27124]);
+/// assert_eq!(sizes, [5, 125]);
+/// ```
+pub fn extract_columns<const N: usize>(
+    buffer: &[u8],
+    delimiter: u8,
+    quote: u8,
+    columns: &[usize],
+) -> Result<Vec<Vec<ParseOutput>>, CsvError> {
+    for (i, column) in columns.iter().enumerate() {
+        assert!(
+            !columns[..i].contains(column),
+            "column {} requested twice",
+            column
+        );
+    }
+
+    let tail = PaddedTail::new(buffer);
+    let mut batches: Vec<BatchParser<N>> = columns.iter().map(|_| BatchParser::new()).collect();
+    let mut outputs = vec![Vec::new(); columns.len()];
+
+    let mut row = 0;
+    let mut column = 0;
+    let mut field_start = 0;
+
+    let mut end_field = |start: usize, mut end: usize, ends_row: bool| -> Result<(), CsvError> {
+        if ends_row && end > start && buffer[end - 1] == b'\r' {
+            end -= 1;
+        }
+
+        // A row with nothing in it at all is missing every column, rather than one empty field
+        if ends_row && column == 0 && start == end {
+            if let Some(&missing) = columns.iter().min() {
+                return Err(CsvError {
+                    row,
+                    column: missing,
+                    kind: CsvErrorKind::MissingColumn,
+                });
+            }
+            row += 1;
+            return Ok(());
+        }
+
+        let (start, end) = strip_quotes(buffer, start, end, quote);
+
+        // There are only ever a few columns, so a search is cheaper than a table
+        if let Some(i) = columns.iter().position(|&c| c == column) {
+            let error = |kind| CsvError { row, column, kind };
+            let input = ParseInput::new(tail.window(buffer, start), end - start)
+                .ok_or_else(|| error(CsvErrorKind::FieldTooLong))?;
+
+            // Every row pushes exactly one input to each column, so the index is the row
+            batches[i]
+                .push(input, &mut outputs[i])
+                .map_err(|e| column_error(e, columns[i]))?;
+        }
+
+        if ends_row {
+            if let Some(&missing) = columns.iter().filter(|&&c| c > column).min() {
+                return Err(CsvError {
+                    row,
+                    column: missing,
+                    kind: CsvErrorKind::MissingColumn,
+                });
+            }
+            row += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+        Ok(())
+    };
+
+    // Set to all ones if the previous chunk ended inside of a quote
+    let mut quote_carry = 0;
+    // Whether a delimiter has been seen since the last newline
+    let mut row_open = false;
+    let scanned = for_each_chunk(
+        buffer,
+        [delimiter, b'\n', quote],
+        |chunk_start, [delimiters, newlines, quotes]| {
+            let inside_quotes = prefix_xor(quotes) ^ quote_carry;
+            quote_carry = 0u32.wrapping_sub((inside_quotes >> 15) & 1) & 0xffff;
+
+            let mut structural = (delimiters | newlines) & !inside_quotes;
+            while structural != 0 {
+                let bit = structural.trailing_zeros();
+                let end = chunk_start + bit as usize;
+                let ends_row = (newlines >> bit) & 1 == 1;
+                break_on_error(end_field(field_start, end, ends_row))?;
+                row_open = !ends_row;
+                field_start = end + 1;
+                structural &= structural - 1;
+            }
+            ControlFlow::Continue(())
+        },
+    );
+    if let ControlFlow::Break(error) = scanned {
+        return Err(error);
+    }
+
+    // The last row doesn't need a newline, even if it ends in a delimiter
+    if field_start < buffer.len() || row_open {
+        end_field(field_start, buffer.len(), true)?;
+    }
+
+    for (i, batch) in batches.into_iter().enumerate() {
+        batch
+            .finish(&mut outputs[i])
+            .map_err(|e| column_error(e, columns[i]))?;
+    }
+
+    Ok(outputs)
+}
+
+/// Turns an error from a column's batch into one with the row filled in
+fn column_error(error: ScanError, column: usize) -> CsvError {
+    match error {
+        ScanError::InvalidField { field } => CsvError {
+            row: field,
+            column,
+            kind: CsvErrorKind::InvalidField,
+        },
+        ScanError::FieldTooLong { field } => CsvError {
+            row: field,
+            column,
+            kind: CsvErrorKind::FieldTooLong,
+        },
+    }
+}
+
+/// Removes a matching pair of quotes around the field, if there is one
+#[inline]
+fn strip_quotes(buffer: &[u8], start: usize, end: usize, quote: u8) -> (usize, usize) {
+    if end - start >= 2 && buffer[start] == quote && buffer[end - 1] == quote {
+        (start + 1, end - 1)
+    } else {
+        (start, end)
+    }
+}
+
+/// Sets bit i if there are an odd number of quotes at or before i.
+/// That is everything inside of quotes plus the opening quotes, as in simdjson
+#[inline]
+fn prefix_xor(quotes: u32) -> u32 {
+    let mut inside = quotes;
+    inside ^= inside << 1;
+    inside ^= inside << 2;
+    inside ^= inside << 4;
+    inside ^= inside << 8;
+    inside & 0xffff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mantissas(columns: Vec<Vec<ParseOutput>>) -> Vec<Vec<u64>> {
+        columns
+            .iter()
+            .map(|column| column.iter().map(|o| o.mantissa).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_columns_across_batches() {
+        let mut csv = String::new();
+        for row in 0..21 {
+            csv += &format!("trade{},{}.5,\"x,y\",{}\n", row, row, row * 2);
+        }
+
+        let columns = extract_columns::<8>(csv.as_bytes(), b',', b'"', &[3, 1]).unwrap();
+
+        let doubled: Vec<u64> = (0..21).map(|row| row * 2).collect();
+        let halves: Vec<u64> = (0..21).map(|row| row * 10 + 5).collect();
+        assert_eq!(mantissas(columns), [doubled, halves]);
+    }
+
+    #[test]
+    fn test_quotes_and_crlf() {
+        let csv = b"\"a,\r\nb\";\"1.25\";3\r\n\"\";\"2\";4";
+        let columns = extract_columns::<16>(csv, b';', b'"', &[1, 2]).unwrap();
+        assert_eq!(mantissas(columns), [[125, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn test_trailing_delimiter() {
+        let csv = b"1,2,\n3,4,";
+        let columns = extract_columns::<8>(csv, b',', b'"', &[0, 2]).unwrap();
+        assert_eq!(mantissas(columns), [[1, 3], [0, 0]]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let csv = b"1,2,3\n4,5x,6\n";
+        assert_eq!(
+            extract_columns::<8>(csv, b',', b'"', &[0, 1]),
+            Err(CsvError {
+                row: 1,
+                column: 1,
+                kind: CsvErrorKind::InvalidField
+            })
+        );
+
+        let csv = b"1,2,3\n4,5\n";
+        assert_eq!(
+            extract_columns::<8>(csv, b',', b'"', &[0, 2]),
+            Err(CsvError {
+                row: 1,
+                column: 2,
+                kind: CsvErrorKind::MissingColumn
+            })
+        );
+
+        // An empty row in the middle is missing its columns, not a row of zeros
+        for csv in [&b"1,2,3\n\n4,5,6\n"[..], b"1,2,3\r\n\r\n4,5,6"] {
+            assert_eq!(
+                extract_columns::<8>(csv, b',', b'"', &[2, 1]),
+                Err(CsvError {
+                    row: 1,
+                    column: 1,
+                    kind: CsvErrorKind::MissingColumn
+                })
+            );
+        }
+
+        // The last row is still finished when it ends in a delimiter instead of a newline
+        let csv = b"1,2,3\n4,";
+        assert_eq!(
+            extract_columns::<8>(csv, b',', b'"', &[0, 2]),
+            Err(CsvError {
+                row: 1,
+                column: 2,
+                kind: CsvErrorKind::MissingColumn
+            })
+        );
+
+        // Any column index works, even one far past the end of every row
+        assert_eq!(
+            extract_columns::<8>(b"1,2,3\n", b',', b'"', &[usize::MAX]),
+            Err(CsvError {
+                row: 0,
+                column: usize::MAX,
+                kind: CsvErrorKind::MissingColumn
+            })
+        );
+
+        let csv = b"1,2,3\n4,\"12345678901234567\",6\n";
+        assert_eq!(
+            extract_columns::<8>(csv, b',', b'"', &[1]),
+            Err(CsvError {
+                row: 1,
+                column: 1,
+                kind: CsvErrorKind::FieldTooLong
+            })
+        );
+    }
+}
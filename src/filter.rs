@@ -0,0 +1,95 @@
+//! Scalar helpers for comparing decimals against a threshold.
+//!
+//! The backends compare digit vectors rather than mantissas. Once a lane and the threshold
+//! are shifted to the same scale, the first digit where they differ decides which is larger,
+//! which can be read straight off of the comparison masks
+
+use crate::sum::{scale_shift, MAX_VECTOR_SCALE};
+use crate::ParseOutput;
+
+const MAX_VECTOR_MANTISSA: u64 = 9_999_999_999_999_999;
+
+/// A threshold as right-aligned digits, the same way a lane looks after removing the dot
+pub(crate) struct Threshold {
+    pub(crate) digits: [u8; 16],
+    exponent: u8,
+    zero_mask: u32,
+    // Set if the threshold is bigger than anything that fits in a vector
+    unreachable: bool,
+}
+
+/// How a lane lines up with the threshold
+pub(crate) enum Alignment {
+    /// Shift the lane and the threshold by these many lanes, and compare the digits
+    Shift { lane: u8, threshold: u8 },
+    /// One of them has too many digits to be shifted, which decides the comparison
+    Known(bool),
+}
+
+impl Threshold {
+    pub(crate) fn new(threshold: ParseOutput) -> Self {
+        let ParseOutput {
+            mut mantissa,
+            mut exponent,
+        } = threshold;
+
+        while exponent > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent -= 1;
+        }
+
+        // Lanes have at most 16 digits and 15 decimals. Rounding the threshold up to
+        // fewer decimals doesn't change the result for any lane that has at most that many,
+        // and lanes with more decimals are too small to reach a threshold this long anyway
+        while exponent > 0 && (exponent > MAX_VECTOR_SCALE || mantissa > MAX_VECTOR_MANTISSA) {
+            mantissa = mantissa / 10 + (mantissa % 10 != 0) as u64;
+            exponent -= 1;
+        }
+
+        let mut digits = [0; 16];
+        let mut zero_mask = 0;
+        let mut remaining = mantissa;
+        for i in (0..16).rev() {
+            digits[i] = (remaining % 10) as u8;
+            zero_mask |= ((digits[i] == 0) as u32) << i;
+            remaining /= 10;
+        }
+
+        Threshold {
+            digits,
+            exponent,
+            zero_mask,
+            unreachable: mantissa > MAX_VECTOR_MANTISSA,
+        }
+    }
+
+    /// Lines up a lane with the given exponent with the threshold.
+    /// zero_mask has bit i set if lane i is a zero digit
+    #[inline(always)]
+    pub(crate) fn align(&self, zero_mask: u32, exponent: u8) -> Alignment {
+        if self.unreachable {
+            return Alignment::Known(false);
+        }
+
+        if exponent <= self.exponent {
+            // If the lane can't be scaled up without losing digits, it's past every threshold
+            match scale_shift(zero_mask, exponent, self.exponent) {
+                Some(lane) => Alignment::Shift { lane, threshold: 0 },
+                None => Alignment::Known(true),
+            }
+        } else {
+            match scale_shift(self.zero_mask, self.exponent, exponent) {
+                Some(threshold) => Alignment::Shift { lane: 0, threshold },
+                None => Alignment::Known(false),
+            }
+        }
+    }
+}
+
+/// Whether a lane is at least the threshold, given masks of the digits where
+/// the lane is greater and less than the threshold. The most significant digit is bit 0
+#[inline(always)]
+pub(crate) fn digits_at_least(greater: u32, less: u32) -> bool {
+    // If neither is set they are equal, and both come out as 32
+    greater.trailing_zeros() <= less.trailing_zeros()
+}
@@ -0,0 +1,219 @@
+//! Parses numeric fields like Price (44), OrderQty (38) and LastPx (31) out of FIX messages.
+//!
+//! Fields are `tag=value` separated by SOH. The SOHs and equals signs are found with the
+//! same chunked scanning as [`crate::scanner`], and all of the requested values
+//! are parsed in a single batch.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::scanner::{break_on_error, first_invalid, for_each_chunk, PaddedTail};
+use crate::{parse_decimals, ParseInput, ParseOutput};
+
+/// The field delimiter of FIX
+pub const SOH: u8 = 0x01;
+
+/// Error from parsing FIX fields, with the tag of the offending field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixError {
+    /// The value is longer than 16 bytes
+    TooLong { tag: u32 },
+    /// The value isn't a valid decimal, or is empty
+    InvalidValue { tag: u32 },
+}
+
+impl fmt::Display for FixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixError::TooLong { tag } => write!(f, "tag {} is too long", tag),
+            FixError::InvalidValue { tag } => write!(f, "tag {} is not a decimal", tag),
+        }
+    }
+}
+
+impl std::error::Error for FixError {}
+
+// Filler for the lanes of tags that aren't in the message
+static PADDING_INPUT: [u8; 16] = [b'0'; 16];
+
+/// Finds each of tags in the message and parses their values in one batch.
+/// Tags that aren't in the message are left out of the map, and if a tag
+/// appears more than once only the first is used.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::fix::parse_fix_fields;
+///
+/// let message = b"8=FIX.4.4\x0135=8\x0144=27123.45\x0132=0.5\x0131=27123.4\x0110=123\x01";
+/// let fields = parse_fix_fields(message, &[44, 31, 38]).unwrap();
+///
+/// assert_eq!(fields[&44].mantissa, 2712345);
+/// assert_eq!(fields[&31].mantissa, 271234);
+/// assert!(!fields.contains_key(&38));
+/// ```
+pub fn parse_fix_fields<const N: usize>(
+    message: &[u8],
+    tags: &[u32; N],
+) -> Result<HashMap<u32, ParseOutput>, FixError> {
+    let tail = PaddedTail::new(message);
+    let padding = ParseInput::new(&PADDING_INPUT, 1).unwrap();
+    let mut inputs = [padding; N];
+    let mut found = [false; N];
+
+    let mut field_start = 0;
+    let mut value_start = None;
+
+    let mut end_field = |tag_and_start: Option<(u32, usize)>, end: usize| -> Result<(), FixError> {
+        let Some((tag, start)) = tag_and_start else {
+            return Ok(());
+        };
+        if let Some(lane) = tags.iter().position(|&t| t == tag) {
+            if !found[lane] {
+                // FIX doesn't allow empty values, even though they'd parse as zero
+                if end == start {
+                    return Err(FixError::InvalidValue { tag });
+                }
+                inputs[lane] = ParseInput::new(tail.window(message, start), end - start)
+                    .ok_or(FixError::TooLong { tag })?;
+                found[lane] = true;
+            }
+        }
+        Ok(())
+    };
+
+    let scanned = for_each_chunk(message, [SOH, b'='], |chunk_start, [sohs, equals]| {
+        let mut structural = sohs | equals;
+        while structural != 0 {
+            let bit = structural.trailing_zeros();
+            let position = chunk_start + bit as usize;
+            if (sohs >> bit) & 1 == 1 {
+                break_on_error(end_field(value_start.take(), position))?;
+                field_start = position + 1;
+            } else if value_start.is_none() {
+                // Only the first equals in a field ends the tag, the rest are in the value
+                value_start = parse_tag(&message[field_start..position]).map(|t| (t, position + 1));
+            }
+            structural &= structural - 1;
+        }
+        ControlFlow::Continue(())
+    });
+    if let ControlFlow::Break(error) = scanned {
+        return Err(error);
+    }
+    // The last field doesn't need a trailing SOH
+    end_field(value_start, message.len())?;
+
+    let mut outputs = [ParseOutput::default(); N];
+    if !parse_decimals::<N, false>(&inputs, &mut outputs) {
+        return Err(FixError::InvalidValue {
+            tag: tags[first_invalid(&inputs).unwrap_or(0)],
+        });
+    }
+
+    Ok((0..N)
+        .filter(|&lane| found[lane])
+        .map(|lane| (tags[lane], outputs[lane]))
+        .collect())
+}
+
+/// Tags are positive integers without leading zeros, which fit in a u32
+#[inline]
+fn parse_tag(tag: &[u8]) -> Option<u32> {
+    if tag.is_empty() || tag.len() > 9 || tag[0] == b'0' {
+        return None;
+    }
+    tag.iter().try_fold(0, |acc, &c| {
+        c.is_ascii_digit().then(|| acc * 10 + (c - b'0') as u32)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fix_message(fields: &[&str]) -> Vec<u8> {
+        fields.join("\x01").into_bytes()
+    }
+
+    #[test]
+    fn test_fields() {
+        let message = fix_message(&[
+            "8=FIX.4.4",
+            "9=120",
+            "35=D",
+            "49=SENDER=WITH=EQUALS",
+            "38=1000",
+            "40=2",
+            "44=0.00012345",
+            "144=1.5",
+            "44=99",
+        ]);
+
+        let fields = parse_fix_fields(&message, &[44, 38, 31, 144]).unwrap();
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(
+            fields[&44],
+            ParseOutput {
+                mantissa: 12345,
+                exponent: 8
+            }
+        );
+        assert_eq!(fields[&38].mantissa, 1000);
+        assert_eq!(fields[&144].mantissa, 15);
+    }
+
+    #[test]
+    fn test_errors() {
+        let message = fix_message(&["35=8", "44=12.x", "38=5"]);
+        assert_eq!(
+            parse_fix_fields(&message, &[38, 44]),
+            Err(FixError::InvalidValue { tag: 44 })
+        );
+
+        let message = fix_message(&["35=8", "31=12345678901234567", "38=5"]);
+        assert_eq!(
+            parse_fix_fields(&message, &[38, 31]),
+            Err(FixError::TooLong { tag: 31 })
+        );
+
+        let message = fix_message(&["35=8", "44=", "38=5"]);
+        assert_eq!(
+            parse_fix_fields(&message, &[38, 44]),
+            Err(FixError::InvalidValue { tag: 44 })
+        );
+
+        // Unrequested fields aren't checked
+        let message = fix_message(&["35=8", "31=12345678901234567", "38=5"]);
+        assert!(parse_fix_fields(&message, &[38]).is_ok());
+    }
+
+    #[test]
+    fn test_many_tags() {
+        // More tags than validate_decimals can report on, with the bad one past lane 32
+        let tags: [u32; 40] = std::array::from_fn(|i| 100 + i as u32);
+        let mut fields: Vec<String> = tags.iter().map(|tag| format!("{}=1.5", tag)).collect();
+        let message = |fields: &[String]| fields.join("\x01").into_bytes();
+        assert_eq!(
+            parse_fix_fields(&message(&fields), &tags).unwrap().len(),
+            40
+        );
+
+        fields[35] = "135=1.x".to_string();
+        assert_eq!(
+            parse_fix_fields(&message(&fields), &tags),
+            Err(FixError::InvalidValue { tag: 135 })
+        );
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(parse_tag(b"44"), Some(44));
+        assert_eq!(parse_tag(b"044"), None);
+        assert_eq!(parse_tag(b""), None);
+        assert_eq!(parse_tag(b"4x"), None);
+        assert_eq!(parse_tag(b"1234567890"), None);
+    }
+}
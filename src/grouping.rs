@@ -0,0 +1,44 @@
+//! Scalar helpers for validating thousands separators.
+//! The separator positions come out of a vector comparison as a bitmask,
+//! where bit i is set if byte i of the right-aligned number is a separator
+
+use crate::DecimalLen;
+
+// There can be at most 3 separators in 16 bytes, "999,999,999,999" is 15 long
+// and another group would need 19
+const MAX_GROUPS: u32 = 3;
+
+// Bitmask of correctly spaced separators, indexed by how many there are
+const GROUP_PATTERN: [u32; MAX_GROUPS as usize + 1] = [0, 0b1, 0b1_0001, 0b1_0001_0001];
+
+/// Returns true if every separator is followed by exactly three digits before
+/// the dot (or the end of the number), and the leading group has one to three digits.
+/// No separators at all is always valid.
+#[inline(always)]
+pub(crate) fn grouping_is_valid(group_mask: u32, dot_idx: u32, real_length: DecimalLen) -> bool {
+    if group_mask == 0 {
+        return true;
+    }
+
+    let count = group_mask.count_ones();
+    let lowest = group_mask.trailing_zeros() as i32;
+    let start = 16 - real_length.get() as i32;
+
+    // The last separator comes right before the last three digits of the integer part
+    let last_group = dot_idx as i32 - 4;
+
+    count <= MAX_GROUPS
+        && group_mask == GROUP_PATTERN[count as usize] << lowest
+        && lowest + 4 * (count as i32 - 1) == last_group
+        && (1..=3).contains(&(lowest - start))
+}
+
+/// Index into [`crate::tables::GROUP_SHUFFLE_CONTROL`] which removes the separators.
+/// Only meaningful for masks that pass [`grouping_is_valid`], but always in bounds
+#[inline(always)]
+pub(crate) fn group_shuffle_index(group_mask: u32) -> usize {
+    let count = group_mask.count_ones().min(MAX_GROUPS);
+    // an empty mask has 32 trailing zeros, which wraps around to the identity shuffle
+    let lowest = group_mask.trailing_zeros() % 16;
+    (count * 16 + lowest) as usize
+}
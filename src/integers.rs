@@ -0,0 +1,35 @@
+//! Scalar helpers for the plain integer entry points
+
+/// u32::MAX has ten digits
+pub(crate) const MAX_U32_DIGITS: usize = 10;
+
+/// Numbers with at most this many digits have nothing in the top half of the reduction
+pub(crate) const HALF_DIGITS: usize = 8;
+
+/// Combines the top and bottom eight digits of a number with at most ten digits.
+/// Returns None if it doesn't fit in a u32
+#[inline(always)]
+pub(crate) fn u32_from_halves(large_half: u32, small_half: u32) -> Option<u32> {
+    large_half.checked_mul(100000000)?.checked_add(small_half)
+}
+
+/// Replaces a leading minus sign with a zero, and returns whether there was one.
+/// A lone minus sign is left in place so that it fails the digit check
+#[inline(always)]
+pub(crate) fn remove_minus_sign(data: &mut [u8; 16], length: usize) -> bool {
+    let negative = length > 1 && data[0] == b'-';
+    if negative {
+        data[0] = b'0';
+    }
+    negative
+}
+
+/// The longest input that gets a u32 to itself when several are gathered into one vector
+pub(crate) const MAX_SMALL_LENGTH: usize = 4;
+
+/// How many small inputs fit in one vector
+pub(crate) const SMALL_PER_VECTOR: usize = 4;
+
+/// Added to a shuffle control from [`crate::tables::SMALL_SHIFT_CONTROL`] to point it at
+/// the bytes of each u32 in turn
+pub(crate) const SMALL_LANE_OFFSET: u32 = 0x0404_0404;
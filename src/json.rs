@@ -0,0 +1,242 @@
+//! Builds [`ParseInput`]s out of numbers inside of JSON, for feeds that send
+//! prices as strings like `"price":"27123.45000000"` as well as bare numbers.
+//!
+//! This doesn't parse JSON. The caller finds where each value starts, which is usually
+//! already known from a tape or a fixed message layout, and this finds where it ends.
+
+use std::fmt;
+
+use crate::scanner::{BatchParser, PaddedTail, ScanError};
+use crate::{find_byte_masks, ParseInput, ParseOutput};
+
+/// Error from reading a JSON number, with the byte offset of the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonError {
+    /// The offset is past the end of the buffer
+    OutOfBounds { offset: usize },
+    /// There's no closing quote or terminator within 16 bytes of the start
+    TooLong { offset: usize },
+    /// The buffer ends before the closing quote of a string
+    UnterminatedString { offset: usize },
+    /// The value isn't a valid decimal
+    InvalidNumber { offset: usize },
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::OutOfBounds { offset } => write!(f, "offset {} is out of bounds", offset),
+            JsonError::TooLong { offset } => write!(f, "value at {} is too long", offset),
+            JsonError::UnterminatedString { offset } => {
+                write!(f, "string at {} has no closing quote", offset)
+            }
+            JsonError::InvalidNumber { offset } => {
+                write!(f, "value at {} is not a decimal", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+// Anything that can come right after a bare number in valid JSON
+const BARE_TERMINATORS: [u8; 7] = [b',', b']', b'}', b' ', b'\t', b'\n', b'\r'];
+
+/// A JSON buffer which numbers can be read out of at any offset,
+/// including right at the end of the buffer
+pub struct JsonNumbers<'a> {
+    buffer: &'a [u8],
+    tail: PaddedTail,
+}
+
+impl<'a> JsonNumbers<'a> {
+    pub fn new(buffer: &'a [u8]) -> Self {
+        JsonNumbers {
+            buffer,
+            tail: PaddedTail::new(buffer),
+        }
+    }
+
+    /// Builds the input for the value starting at offset.
+    /// If the value is a string, the input is what's between the quotes.
+    /// Otherwise it is a bare number, which ends at a comma, closing bracket, whitespace,
+    /// or the end of the buffer. An empty value like `""` isn't a number, so it's rejected
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// use simd_decimal::json::JsonNumbers;
+    /// use simd_decimal::{parse_decimals, ParseOutput};
+    ///
+    /// let json = br#"{"p":"27123.45000000","q":0.5}"#;
+    /// let numbers = JsonNumbers::new(json);
+    /// let inputs = [numbers.input_at(5).unwrap(), numbers.input_at(26).unwrap()];
+    ///
+    /// let mut outputs = [ParseOutput::default(); 2];
+    /// assert!(parse_decimals::<2, false>(&inputs, &mut outputs));
+    /// assert_eq!(outputs[0].mantissa, 2712345000000);
+    /// assert_eq!(outputs[1].mantissa, 5);
+    /// ```
+    pub fn input_at(&self, offset: usize) -> Result<ParseInput<'_>, JsonError> {
+        let first = *self
+            .buffer
+            .get(offset)
+            .ok_or(JsonError::OutOfBounds { offset })?;
+
+        let quoted = first == b'"';
+        let start = offset + quoted as usize;
+        let window = self.tail.window(self.buffer, start);
+        let remaining = self.buffer.len() - start;
+
+        let terminators = unsafe {
+            if quoted {
+                find_byte_masks(window, [b'"'])[0]
+            } else {
+                find_byte_masks(window, BARE_TERMINATORS)
+                    .iter()
+                    .fold(0, |acc, mask| acc | mask)
+            }
+        };
+
+        let real_length = if terminators != 0 {
+            // The padding past the end of the buffer is never a terminator
+            (terminators.trailing_zeros() as usize).min(remaining)
+        } else {
+            // The value might fill the whole window, with the terminator just past it
+            match self.buffer.get(start + 16) {
+                Some(b'"') if quoted => 16,
+                Some(next) if !quoted && BARE_TERMINATORS.contains(next) => 16,
+                None if !quoted && remaining <= 16 => remaining,
+                None if quoted && remaining <= 16 => {
+                    return Err(JsonError::UnterminatedString { offset })
+                }
+                _ => return Err(JsonError::TooLong { offset }),
+            }
+        };
+
+        if real_length == 0 {
+            return Err(JsonError::InvalidNumber { offset });
+        }
+
+        Ok(ParseInput::new(window, real_length).unwrap())
+    }
+}
+
+/// Parses the values at each of offsets in batches of N, and appends them to outputs.
+/// This is for parsing everything in a message at once, like all the levels of a depth update.
+/// On error outputs may contain some of the values, but which ones is unspecified
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::json::parse_json_numbers;
+///
+/// let json = br#"[["27123.45","0.5"],["27123.5",1.25]]"#;
+/// let mut outputs = Vec::new();
+/// parse_json_numbers::<8>(json, &[2, 13, 21, 31], &mut outputs).unwrap();
+///
+/// let mantissas: Vec<u64> = outputs.iter().map(|o| o.mantissa).collect();
+/// assert_eq!(mantissas, [2712345, 5, 271235, 125]);
+/// ```
+pub fn parse_json_numbers<const N: usize>(
+    buffer: &[u8],
+    offsets: &[usize],
+    outputs: &mut Vec<ParseOutput>,
+) -> Result<(), JsonError> {
+    let numbers = JsonNumbers::new(buffer);
+    let mut batch = BatchParser::<N>::new();
+
+    let invalid = |error| match error {
+        ScanError::InvalidField { field } | ScanError::FieldTooLong { field } => {
+            JsonError::InvalidNumber {
+                offset: offsets[field],
+            }
+        }
+    };
+
+    for &offset in offsets {
+        batch
+            .push(numbers.input_at(offset)?, outputs)
+            .map_err(invalid)?;
+    }
+    batch.finish(outputs).map_err(invalid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(json: &[u8], offset: usize) -> Result<Vec<u8>, JsonError> {
+        let numbers = JsonNumbers::new(json);
+        let input = numbers.input_at(offset)?;
+        Ok(input.data[..input.real_length.get()].to_vec())
+    }
+
+    #[test]
+    fn test_terminators() {
+        let json = b"[\"1.5\",2.25 ,3\n,4}";
+        assert_eq!(read(json, 1).unwrap(), b"1.5");
+        assert_eq!(read(json, 7).unwrap(), b"2.25");
+        assert_eq!(read(json, 13).unwrap(), b"3");
+        assert_eq!(read(json, 16).unwrap(), b"4");
+    }
+
+    #[test]
+    fn test_end_of_buffer() {
+        assert_eq!(read(b"12.5", 0).unwrap(), b"12.5");
+        assert_eq!(read(b"1234567890123456", 0).unwrap(), b"1234567890123456");
+        assert_eq!(
+            read(b"\"1234567890123456\"", 0).unwrap(),
+            b"1234567890123456"
+        );
+        assert_eq!(read(b"1", 1), Err(JsonError::OutOfBounds { offset: 1 }));
+    }
+
+    #[test]
+    fn test_unterminated() {
+        for json in [&b"\"12"[..], b"\"", b"\"1234567890123456"] {
+            assert_eq!(
+                read(json, 0),
+                Err(JsonError::UnterminatedString { offset: 0 })
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(
+            read(b"\"\"", 0),
+            Err(JsonError::InvalidNumber { offset: 0 })
+        );
+        assert_eq!(
+            read(b"[,1]", 1),
+            Err(JsonError::InvalidNumber { offset: 1 })
+        );
+    }
+
+    #[test]
+    fn test_too_long() {
+        assert_eq!(
+            read(b"\"12345678901234567\"", 0),
+            Err(JsonError::TooLong { offset: 0 })
+        );
+        assert_eq!(
+            read(b"12345678901234567", 0),
+            Err(JsonError::TooLong { offset: 0 })
+        );
+        assert_eq!(
+            read(b"\"12345678901234567", 0),
+            Err(JsonError::TooLong { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_in_batch() {
+        let json = br#"["1","2","x","4"]"#;
+        let mut outputs = Vec::new();
+        assert_eq!(
+            parse_json_numbers::<2>(json, &[1, 5, 9, 13], &mut outputs),
+            Err(JsonError::InvalidNumber { offset: 9 })
+        );
+    }
+}
@@ -4,26 +4,60 @@
 #[cfg(target_arch = "x86_64")]
 mod parser_sse;
 #[cfg(target_arch = "x86_64")]
-use parser_sse::parse_decimals;
+use parser_sse::{
+    do_filter_decimals, do_parse_decimals, do_parse_decimals_columnar, do_parse_decimals_grouped,
+    do_parse_decimals_strict, do_parse_decimals_utf16, do_parse_decimals_with_separator,
+    do_parse_i64s, do_parse_implied, do_parse_packed_decimals, do_parse_small_integers,
+    do_parse_u32s, do_parse_u64s, do_parse_zoned_decimals, do_sort_key_decimals, do_sum_decimals,
+    do_validate_decimals, find_byte_masks,
+};
 
 #[cfg(target_arch = "aarch64")]
 mod parser_aarch64;
 #[cfg(target_arch = "aarch64")]
 pub use parser_aarch64::do_parse_decimals;
+#[cfg(target_arch = "aarch64")]
+use parser_aarch64::{
+    do_filter_decimals, do_parse_decimals_columnar, do_parse_decimals_grouped,
+    do_parse_decimals_strict, do_parse_decimals_utf16, do_parse_decimals_with_separator,
+    do_parse_i64s, do_parse_implied, do_parse_packed_decimals, do_parse_small_integers,
+    do_parse_u32s, do_parse_u64s, do_parse_zoned_decimals, do_sort_key_decimals, do_sum_decimals,
+    do_validate_decimals, find_byte_masks,
+};
 
+mod arithmetic;
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+pub mod book;
+mod cobol;
+mod columns;
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+pub mod csv;
+mod filter;
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+pub mod fix;
+mod grouping;
+mod integers;
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+pub mod json;
+mod ordering;
+mod padding;
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+pub mod scanner;
+mod short;
+mod strict;
+mod sum;
 mod tables;
 
+pub use arithmetic::{validate_ticks, RoundingMode};
+pub use ordering::NormalizedDecimal;
+
 /// Parses the inputs passed into (mantissa, exponent) pairs.
 /// If any of them detected invalid, returns false
 ///
 /// No doctests for this dummy wrapper since they'll fail on unsupported architectures
-///
-/// # Safety
-///
-/// It is unsafe to pass anything with a real_length that is greater than 16
 #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
 #[inline]
-pub unsafe fn parse_decimals<const N: usize, const I: bool>(
+pub fn parse_decimals<const N: usize, const I: bool>(
     _: &[ParseInput; N],
     _: &mut [ParseOutput; N],
 ) -> bool {
@@ -35,12 +69,12 @@ pub unsafe fn parse_decimals<const N: usize, const I: bool>(
 /// Examples:
 ///
 /// ```
-/// let data = b"987654321.123_..";
-/// let real_length = 13;
-/// let input = ParseInput { data, real_length };
+/// use simd_decimal::*;
+///
+/// let input = ParseInput::new(b"987654321.123_..", 13).unwrap();
 /// let mut output = [ParseOutput::default()];
 ///
-/// let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+/// let was_good = parse_decimals::<1, false>(&[input], &mut output);
 ///
 /// assert!(was_good);
 /// assert_eq!(
@@ -58,243 +92,1918 @@ pub fn parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     inputs: &[ParseInput; N],
     outputs: &mut [ParseOutput; N],
 ) -> bool {
-    if inputs.iter().any(|i| i.real_length > 16) {
+    unsafe { do_parse_decimals::<N, KNOWN_INTEGER>(inputs, outputs) }
+}
+
+/// Parses the inputs passed into (mantissa, exponent) pairs, and returns false if one is detected to be invalid
+///
+/// Lengths are validated when the [`DecimalLen`] is built, so this no longer has a
+/// safety contract and is identical to [`parse_decimals`]. It's kept around for existing callers.
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_unchecked<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    parse_decimals::<N, KNOWN_INTEGER>(inputs, outputs)
+}
+
+/// Checks which of the inputs are well-formed decimals, without producing any outputs.
+/// This accepts exactly what [`parse_decimals`] accepts, but skips the conversion to integers.
+///
+/// Returns a mask with bit i set if input i is valid, so N can be at most 32
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"123.45__________", 6).unwrap(),
+///     ParseInput::new(b"12x.45__________", 6).unwrap(),
+///     ParseInput::new(b".5______________", 2).unwrap(),
+/// ];
+///
+/// assert_eq!(validate_decimals(&inputs), 0b101);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn validate_decimals<const N: usize>(inputs: &[ParseInput; N]) -> u32 {
+    assert!(N <= 32, "validate_decimals returns a u32 mask");
+    unsafe { do_validate_decimals::<N>(inputs) }
+}
+
+/// Returns a mask with bit i set if input i is at least threshold, comparing by value
+/// so that "1.50" and "1.5" are equal.
+///
+/// Inputs that fail to parse are never in the returned mask, and are set in invalid instead.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"27123.45________", 8).unwrap(),
+///     ParseInput::new(b"27000___________", 5).unwrap(),
+///     ParseInput::new(b"27100.00________", 8).unwrap(),
+///     ParseInput::new(b"2712x.45________", 8).unwrap(),
+/// ];
+/// let mut invalid = 0;
+///
+/// // 27100 at a scale of 0 is the same value as 27100.00
+/// let threshold = ParseOutput { mantissa: 27100, exponent: 0 };
+/// assert_eq!(filter_decimals::<4>(&inputs, threshold, &mut invalid), 0b101);
+/// assert_eq!(invalid, 0b1000);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn filter_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    threshold: ParseOutput,
+    invalid: &mut u32,
+) -> u32 {
+    assert!(N <= 32, "filter_decimals returns a u32 mask");
+    let (at_least, valid) = unsafe { do_filter_decimals::<N>(inputs, threshold) };
+    *invalid = !valid & ((1u64 << N) - 1) as u32;
+    at_least
+}
+
+/// Parses the inputs like [`parse_decimals`] into u64 keys, which sort as unsigned integers
+/// in the same order as the values. This is for radix sorting price columns.
+///
+/// Every value is scaled to scale decimals, which must be at least as many as any input has.
+/// Keys have the sign bit flipped, so they can be sorted together with the keys of negative
+/// values from [`SignedParseOutput::sort_key`].
+///
+/// Returns false if any input is invalid, has more than scale decimals,
+/// or doesn't fit in an i64 at scale.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"27123.45________", 8).unwrap(),
+///     ParseInput::new(b"27123.4_________", 7).unwrap(),
+///     ParseInput::new(b"9999____________", 4).unwrap(),
+/// ];
+/// let mut keys = [0; 3];
+///
+/// assert!(sort_key_decimals::<3>(&inputs, &mut keys, 4));
+/// assert!(keys[2] < keys[1] && keys[1] < keys[0]);
+///
+/// let negative = SignedParseOutput { mantissa: -1, exponent: 0 };
+/// assert!(negative.sort_key(4).unwrap() < keys[2]);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn sort_key_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    keys: &mut [u64; N],
+    scale: u8,
+) -> bool {
+    unsafe { do_sort_key_decimals::<N>(inputs, keys, scale) }
+}
+
+/// Parses the inputs like [`parse_decimals`] and adds them to total, which has scale decimals.
+/// This is exact, and doesn't multiply every value by a power of ten to rescale it like
+/// parsing and then adding would.
+///
+/// Returns false and leaves total alone if any input is invalid, has more than
+/// scale decimals, or the sum doesn't fit in an i128.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"27123.45________", 8).unwrap(),
+///     ParseInput::new(b"0.125___________", 5).unwrap(),
+///     ParseInput::new(b"100_____________", 3).unwrap(),
+/// ];
+/// let mut total = 0;
+///
+/// assert!(sum_decimals::<3>(&inputs, 4, &mut total));
+/// assert_eq!(total, 272235750);
+///
+/// // 0.125 needs three decimals
+/// assert!(!sum_decimals::<3>(&inputs, 2, &mut total));
+/// assert_eq!(total, 272235750);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn sum_decimals<const N: usize>(inputs: &[ParseInput; N], scale: u8, total: &mut i128) -> bool {
+    match unsafe { do_sum_decimals::<N>(inputs, scale) }.and_then(|sum| total.checked_add(sum)) {
+        Some(sum) => {
+            *total = sum;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Parses fixed-width integer fields with an implied number of decimals, like "000001234567"
+/// with a scale of 4 meaning 123.4567. Every output gets SCALE as its exponent.
+///
+/// Fields may be padded on the left with zeros or spaces, but can't contain a dot.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"000001234567____", 12).unwrap(),
+///     ParseInput::new(b"     1234567____", 12).unwrap(),
+/// ];
+/// let mut outputs = [ParseOutput::default(); 2];
+///
+/// assert!(parse_implied::<2, 4>(&inputs, &mut outputs));
+/// assert_eq!(outputs[0], ParseOutput { mantissa: 1234567, exponent: 4 });
+/// assert_eq!(outputs[0], outputs[1]);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_implied<const N: usize, const SCALE: u8>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    unsafe { do_parse_implied::<N>(inputs, outputs, SCALE) }
+}
+
+/// Parses the inputs like [`parse_decimals`], then rescales every output to have scale decimals.
+/// Extra decimals are rounded according to mode, and missing ones are filled with zeros.
+///
+/// Returns false if any input is invalid, is rejected by [`RoundingMode::Reject`],
+/// or doesn't fit in a u64 at scale
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"123.456789______", 10).unwrap(),
+///     ParseInput::new(b"9.99995_________", 7).unwrap(),
+///     ParseInput::new(b"12.5____________", 4).unwrap(),
+/// ];
+/// let mut outputs = [ParseOutput::default(); 3];
+///
+/// assert!(parse_decimals_rescaled::<3>(&inputs, &mut outputs, 4, RoundingMode::HalfEven));
+/// assert_eq!(outputs.map(|o| o.mantissa), [1234568, 100000, 125000]);
+/// assert!(outputs.iter().all(|o| o.exponent == 4));
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_rescaled<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    scale: u8,
+    mode: RoundingMode,
+) -> bool {
+    if !parse_decimals::<N, false>(inputs, outputs) {
         return false;
     }
-    unsafe { parse_decimals_unchecked::<N, KNOWN_INTEGER>(inputs, outputs) }
+
+    let mut all_good = true;
+    for output in outputs.iter_mut() {
+        match output.rescale(scale, mode) {
+            Some(rescaled) => *output = rescaled,
+            None => all_good = false,
+        }
+    }
+    all_good
 }
 
-/// Parses the inputs passed into (mantissa, exponent) pairs, and returns false if one is detected to be invalid
+/// Parses COBOL packed decimals (COMP-3), which hold two digits per byte with the sign
+/// in the last nibble. real_length is the number of bytes, so 1 to 8 bytes holding up to 15 digits.
+/// Every output gets scale as its exponent.
+///
+/// C, A, E and F are positive signs, and D and B are negative.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
 ///
-/// # Safety
+/// let inputs = [
+///     ParseInput::new(&[0x12, 0x34, 0x5c, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 3).unwrap(),
+///     ParseInput::new(&[0x00, 0x7d, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 2).unwrap(),
+/// ];
+/// let mut outputs = [SignedParseOutput::default(); 2];
 ///
-/// It is unsafe to pass an input with real_length longer than 16
+/// assert!(parse_packed_decimals::<2>(&inputs, &mut outputs, 2));
+/// assert_eq!(outputs[0], SignedParseOutput { mantissa: 12345, exponent: 2 });
+/// assert_eq!(outputs[1], SignedParseOutput { mantissa: -7, exponent: 2 });
+/// ```
 #[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
 #[inline]
-pub unsafe fn parse_decimals_unchecked<const N: usize, const KNOWN_INTEGER: bool>(
+pub fn parse_packed_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    scale: u8,
+) -> bool {
+    unsafe { do_parse_packed_decimals::<N>(inputs, outputs, scale) }
+}
+
+/// Parses COBOL zoned decimals, which are fixed-width digits with the sign overpunched
+/// onto the last one. Every output gets scale as its exponent.
+///
+/// The last byte is `{` or `A` to `I` for positive 0 to 9, and `}` or `J` to `R`
+/// for negative 0 to 9. A plain digit there is unsigned, and is read as positive.
+///
+/// This is the ASCII form of the overpunch. Raw EBCDIC fields are parsed by
+/// [`parse_zoned_decimals_ebcdic`].
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"0001234E________", 8).unwrap(),
+///     ParseInput::new(b"0001234N________", 8).unwrap(),
+/// ];
+/// let mut outputs = [SignedParseOutput::default(); 2];
+///
+/// assert!(parse_zoned_decimals::<2>(&inputs, &mut outputs, 2));
+/// assert_eq!(outputs[0], SignedParseOutput { mantissa: 12345, exponent: 2 });
+/// assert_eq!(outputs[1], SignedParseOutput { mantissa: -12345, exponent: 2 });
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_zoned_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    scale: u8,
+) -> bool {
+    unsafe { do_parse_zoned_decimals::<N, false>(inputs, outputs, scale) }
+}
+
+/// Parses COBOL zoned decimals like [`parse_zoned_decimals`], but as raw EBCDIC.
+///
+/// The digits are 0xF0 to 0xF9, and the zone nibble of the last byte is the sign.
+/// 0xC0 to 0xC9 are positive 0 to 9 and 0xD0 to 0xD9 are negative 0 to 9.
+/// A plain digit there is unsigned, and is read as positive.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(&[0xF1, 0xF2, 0xC3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 3).unwrap(),
+///     ParseInput::new(&[0xF1, 0xF2, 0xD3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 3).unwrap(),
+/// ];
+/// let mut outputs = [SignedParseOutput::default(); 2];
+///
+/// assert!(parse_zoned_decimals_ebcdic::<2>(&inputs, &mut outputs, 1));
+/// assert_eq!(outputs[0], SignedParseOutput { mantissa: 123, exponent: 1 });
+/// assert_eq!(outputs[1], SignedParseOutput { mantissa: -123, exponent: 1 });
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_zoned_decimals_ebcdic<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    scale: u8,
+) -> bool {
+    unsafe { do_parse_zoned_decimals::<N, true>(inputs, outputs, scale) }
+}
+
+/// Parses the inputs like [`parse_decimals`], but only accepts numbers matching the
+/// JSON number grammar from RFC 8259.
+///
+/// [`parse_decimals`] is lenient and accepts "", ".5", "5." and "007", which this rejects.
+/// Signs and exponents are never accepted by this crate, so they are rejected here too.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let mut output = [ParseOutput::default()];
+///
+/// let good = ParseInput::new(b"0.5_____________", 3).unwrap();
+/// assert!(parse_decimals_strict::<1, false>(&[good], &mut output));
+///
+/// let bad = ParseInput::new(b"007_____________", 3).unwrap();
+/// assert!(!parse_decimals_strict::<1, false>(&[bad], &mut output));
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_strict<const N: usize, const KNOWN_INTEGER: bool>(
     inputs: &[ParseInput; N],
     outputs: &mut [ParseOutput; N],
 ) -> bool {
-    unsafe { do_parse_decimals::<N, KNOWN_INTEGER>(inputs, outputs) }
+    unsafe { do_parse_decimals_strict::<N, KNOWN_INTEGER>(inputs, outputs) }
+}
+
+/// Parses the inputs like [`parse_decimals`], but writes the mantissas and exponents
+/// into separate arrays. `ParseOutput` pads out to 16 bytes, so this is the better fit
+/// for columnar storage. With `KNOWN_INTEGER` every exponent is set to zero.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"123.45__________", 6).unwrap(),
+///     ParseInput::new(b"7_______________", 1).unwrap(),
+/// ];
+/// let mut mantissas = [0; 2];
+/// let mut exponents = [0; 2];
+///
+/// assert!(parse_decimals_columnar::<2, false>(&inputs, &mut mantissas, &mut exponents));
+/// assert_eq!(mantissas, [12345, 7]);
+/// assert_eq!(exponents, [2, 0]);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_columnar<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    mantissas: &mut [u64; N],
+    exponents: &mut [u8; N],
+) -> bool {
+    unsafe { do_parse_decimals_columnar::<N, KNOWN_INTEGER>(inputs, mantissas, exponents) }
+}
+
+/// Parses the inputs as plain unsigned integers, with no unpacking from [`ParseOutput`].
+/// This accepts exactly what `parse_decimals::<N, true>` does.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"1000____________", 4).unwrap(),
+///     ParseInput::new(b"9999999999999999", 16).unwrap(),
+/// ];
+/// let mut outputs = [0; 2];
+///
+/// assert!(parse_u64s::<2>(&inputs, &mut outputs));
+/// assert_eq!(outputs, [1000, 9999999999999999]);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_u64s<const N: usize>(inputs: &[ParseInput; N], outputs: &mut [u64; N]) -> bool {
+    unsafe { do_parse_u64s::<N>(inputs, outputs) }
+}
+
+/// Parses the inputs as plain unsigned integers like [`parse_u64s`].
+/// Returns false if any input has more than ten digits, including leading zeros,
+/// or doesn't fit in a u32.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let mut outputs = [0; 1];
+///
+/// let max = ParseInput::new(b"4294967295______", 10).unwrap();
+/// assert!(parse_u32s::<1>(&[max], &mut outputs));
+/// assert_eq!(outputs, [u32::MAX]);
+///
+/// let too_big = ParseInput::new(b"4294967296______", 10).unwrap();
+/// assert!(!parse_u32s::<1>(&[too_big], &mut outputs));
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_u32s<const N: usize>(inputs: &[ParseInput; N], outputs: &mut [u32; N]) -> bool {
+    unsafe { do_parse_u32s::<N>(inputs, outputs) }
+}
+
+/// Parses the inputs as plain integers like [`parse_u64s`], which may start with a minus sign.
+/// The sign counts towards the length, so negative inputs have at most 15 digits.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"-42_____________", 3).unwrap(),
+///     ParseInput::new(b"42______________", 2).unwrap(),
+/// ];
+/// let mut outputs = [0; 2];
+///
+/// assert!(parse_i64s::<2>(&inputs, &mut outputs));
+/// assert_eq!(outputs, [-42, 42]);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_i64s<const N: usize>(inputs: &[ParseInput; N], outputs: &mut [i64; N]) -> bool {
+    unsafe { do_parse_i64s::<N>(inputs, outputs) }
+}
+
+/// Parses up to four plain integers of at most 4 bytes like [`parse_u32s`], gathered into a
+/// single vector with one shuffle. This is for timestamps, sizes and other small fields,
+/// where a whole vector per number is mostly padding.
+///
+/// Returns false if any input is invalid or longer than 4 bytes.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"2024____________", 4).unwrap(),
+///     ParseInput::new(b"12______________", 2).unwrap(),
+///     ParseInput::new(b"0007____________", 4).unwrap(),
+/// ];
+/// let mut outputs = [0; 3];
+///
+/// assert!(parse_small_integers::<3>(&inputs, &mut outputs));
+/// assert_eq!(outputs, [2024, 12, 7]);
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_small_integers<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u32; N],
+) -> bool {
+    assert!(N <= 4, "parse_small_integers fits four inputs in a vector");
+    unsafe { do_parse_small_integers::<N>(inputs, outputs) }
+}
+
+/// Parses inputs of at most 8 bytes like [`parse_decimals`], each with plain integer
+/// arithmetic on a u64 instead of a vector. This doesn't need any vector instructions.
+///
+/// Whether this is faster depends on the target features [`parse_decimals`] is built with.
+/// With the default x86_64 target it is faster at every N up to at least 16, since the
+/// vector parser can't use its ssse3 shuffles inline. With `-C target-cpu=native` it is
+/// about as fast at N = 1 and 16, but almost twice as slow at N = 4 and 8, where the
+/// vector parser handles several lanes per instruction. Benchmark both on the target.
+///
+/// Returns false if any input is invalid or longer than 8 bytes.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let inputs = [
+///     ParseInput::new(b"1234.5__________", 6).unwrap(),
+///     ParseInput::new(b"0.000001________", 8).unwrap(),
+/// ];
+/// let mut outputs = [ParseOutput::default(); 2];
+///
+/// assert!(parse_short_decimals::<2, false>(&inputs, &mut outputs));
+/// assert_eq!(outputs[0], ParseOutput { mantissa: 12345, exponent: 1 });
+/// assert_eq!(outputs[1], ParseOutput { mantissa: 1, exponent: 6 });
+///
+/// let long = ParseInput::new(b"123456.78_______", 9).unwrap();
+/// let mut output = [ParseOutput::default()];
+/// assert!(!parse_short_decimals::<1, false>(&[long], &mut output));
+/// ```
+#[inline]
+pub fn parse_short_decimals<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    short::do_parse_short_decimals::<N, KNOWN_INTEGER>(inputs, outputs)
+}
+
+/// Parses utf-16 inputs like [`parse_decimals`], without transcoding them first.
+/// Any code unit above 0x7F inside of the decimal makes it invalid.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let mut data = [0; 16];
+/// for (unit, c) in data.iter_mut().zip("123.45".encode_utf16()) {
+///     *unit = c;
+/// }
+/// let input = ParseInput16::new(&data, 6).unwrap();
+/// let mut output = [ParseOutput::default()];
+///
+/// assert!(parse_decimals_utf16::<1, false>(&[input], &mut output));
+/// assert_eq!(output[0], ParseOutput { mantissa: 12345, exponent: 2 });
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_utf16<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput16; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    unsafe { do_parse_decimals_utf16::<N, KNOWN_INTEGER>(inputs, outputs) }
+}
+
+/// Parses the inputs like [`parse_decimals`], but with `decimal_separator` in place of the dot.
+/// This is for locales that write "123,45". The separator can't be a digit, a space or a sign,
+/// and every input is rejected if it is.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let input = ParseInput::new(b"123,45;678,9____", 6).unwrap();
+/// let mut output = [ParseOutput::default()];
+///
+/// assert!(parse_decimals_with_separator::<1, false>(&[input], &mut output, b','));
+/// assert_eq!(
+///     output[0],
+///     ParseOutput {
+///         exponent: 2,
+///         mantissa: 12345
+///     }
+/// );
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_with_separator<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    decimal_separator: u8,
+) -> bool {
+    if !is_separator(decimal_separator) {
+        return false;
+    }
+    unsafe {
+        do_parse_decimals_with_separator::<N, KNOWN_INTEGER>(inputs, outputs, decimal_separator)
+    }
+}
+
+/// Whether the byte can separate the parts of a number without being mistaken for one of them
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+fn is_separator(byte: u8) -> bool {
+    !matches!(byte, b'0'..=b'9' | b' ' | b'+' | b'-')
+}
+
+/// Parses the inputs like [`parse_decimals`], but also accepts thousands separators
+/// such as "1,234,567.89" or "1.000.000,5", where `group_separator` is the grouping byte
+/// and `decimal_separator` takes the place of the dot.
+///
+/// Separators are optional, but if there are any they must split the whole integer part
+/// into groups of three digits, with one to three digits in the leading group, and they
+/// can't appear after the decimal separator. Neither separator can be a digit, a space or
+/// a sign, and they must differ. Every input is rejected if they don't.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::*;
+///
+/// let input = ParseInput::new(b"1,234,567.89____", 12).unwrap();
+/// let mut output = [ParseOutput::default()];
+///
+/// assert!(parse_decimals_grouped::<1, false>(&[input], &mut output, b'.', b','));
+/// assert_eq!(
+///     output[0],
+///     ParseOutput {
+///         exponent: 2,
+///         mantissa: 123456789
+///     }
+/// );
+/// ```
+#[cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+#[inline]
+pub fn parse_decimals_grouped<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    decimal_separator: u8,
+    group_separator: u8,
+) -> bool {
+    if !is_separator(decimal_separator)
+        || !is_separator(group_separator)
+        || decimal_separator == group_separator
+    {
+        return false;
+    }
+    unsafe {
+        do_parse_decimals_grouped::<N, KNOWN_INTEGER>(
+            inputs,
+            outputs,
+            decimal_separator,
+            group_separator,
+        )
+    }
+}
+
+/// Length of a decimal inside of a 16 byte window.
+/// This can only be constructed with a value of at most 16,
+/// which is what lets the parsers index the shuffle tables without a bounds check
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DecimalLen(u8);
+
+impl DecimalLen {
+    /// The largest length that fits in a single vector
+    pub const MAX: usize = 16;
+
+    /// Returns None if the length does not fit in a single vector
+    #[inline]
+    pub const fn new(len: usize) -> Option<Self> {
+        if len <= Self::MAX {
+            Some(DecimalLen(len as u8))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub const fn get(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Struct containing descriptors of the input to be parsed.
+/// Specifically this contains a reference to 16 contiguous characters starting with the
+/// decimal itself that are valid to load, as well as the true number length
+#[derive(Clone, Copy, Debug)]
+pub struct ParseInput<'a> {
+    /// Reference to 16 contiguous bytes with the number starting from the least significant bytes
+    /// i.e. if the whole string was "12345.234, random junk bytes"
+    /// this would refer to "12345.234, rando"
+    pub data: &'a [u8; 16],
+
+    /// This is the actual length of the decimal. In the above example,
+    /// with the 16 bytes being "12345.234, rando",
+    /// the real length is 9. &"12345.234, rando"[..9] = "12345.234"
+    pub real_length: DecimalLen,
+}
+
+impl<'a> ParseInput<'a> {
+    /// Returns None if real_length is greater than 16
+    #[inline]
+    pub const fn new(data: &'a [u8; 16], real_length: usize) -> Option<Self> {
+        match DecimalLen::new(real_length) {
+            Some(real_length) => Some(ParseInput { data, real_length }),
+            None => None,
+        }
+    }
+}
+
+/// Same as [`ParseInput`], but for utf-16 strings.
+/// real_length is the number of code units rather than bytes
+#[derive(Clone, Copy, Debug)]
+pub struct ParseInput16<'a> {
+    /// Reference to 16 contiguous code units with the number starting from the first
+    pub data: &'a [u16; 16],
+
+    /// This is the actual length of the decimal in code units
+    pub real_length: DecimalLen,
+}
+
+impl<'a> ParseInput16<'a> {
+    /// Returns None if real_length is greater than 16
+    #[inline]
+    pub const fn new(data: &'a [u16; 16], real_length: usize) -> Option<Self> {
+        match DecimalLen::new(real_length) {
+            Some(real_length) => Some(ParseInput16 { data, real_length }),
+            None => None,
+        }
+    }
+}
+
+/// Equality compares the mantissa and exponent, so "1.5" and "1.50" are different outputs.
+/// Use [`ParseOutput::numeric_eq`] or [`NormalizedDecimal`] to compare by value
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy, Hash)]
+pub struct ParseOutput {
+    pub mantissa: u64,
+    pub exponent: u8,
+}
+
+/// Output of the formats which carry a sign, like COBOL packed and zoned decimals
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
+pub struct SignedParseOutput {
+    pub mantissa: i64,
+    pub exponent: u8,
 }
 
-/// Struct containing descriptors of the input to be parsed.
-/// Specifically this contains a reference to 16 contiguous characters starting with the
-/// decimal itself that are valid to load, as well as the true number length
-#[derive(Clone, Copy, Debug)]
-pub struct ParseInput<'a> {
-    /// Reference to 16 contiguous bytes with the number starting from the least significant bytes
-    /// i.e. if the whole string was "12345.234, random junk bytes"
-    /// this would refer to "12345.234, rando"
-    pub data: &'a [u8; 16],
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    // Every test of the plain parser runs once for each of these
+    const SEPARATORS: [u8; 2] = [b'.', b','];
+
+    fn parse_one(data: &[u8; 16], real_length: usize, separator: u8) -> (bool, ParseOutput) {
+        let data = data.map(|c| if c == b'.' { separator } else { c });
+        let input = ParseInput::new(&data, real_length).unwrap();
+        let mut output = [ParseOutput::default()];
+
+        let was_good = if separator == b'.' {
+            parse_decimals::<1, false>(&[input], &mut output)
+        } else {
+            parse_decimals_with_separator::<1, false>(&[input], &mut output, separator)
+        };
+
+        (was_good, output[0])
+    }
+
+    #[test]
+    fn test_zero() {
+        let data = [b'0'; 16];
+        for separator in SEPARATORS {
+            for real_length in 1..16 {
+                let (was_good, output) = parse_one(&data, real_length, separator);
+
+                assert!(was_good);
+                assert_eq!(
+                    output,
+                    ParseOutput {
+                        exponent: 0,
+                        mantissa: 0
+                    }
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_length_too_long() {
+        assert!(DecimalLen::new(16).is_some());
+        assert!(DecimalLen::new(17).is_none());
+        assert!(ParseInput::new(&[b'0'; 16], 17).is_none());
+    }
+
+    #[test]
+    fn test_a_big_decimal() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b"987654321.123_..", 13, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 3,
+                    mantissa: 987654321123
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_a_big_integer() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b"987654321123_..9", 12, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 0,
+                    mantissa: 987654321123
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_full_sized_integer() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b"1234567898765432", 16, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 0,
+                    mantissa: 1234567898765432
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_integer() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b"9999999999999999", 16, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 0,
+                    mantissa: 9999999999999999
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_decimal() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b".000000000000001", 16, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 15,
+                    mantissa: 1
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_dot_at_end() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b"987654321.------", 10, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 0,
+                    mantissa: 987654321
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_dot_at_start() {
+        for separator in SEPARATORS {
+            let (was_good, output) = parse_one(b".987654321------", 10, separator);
+
+            assert!(was_good);
+            assert_eq!(
+                output,
+                ParseOutput {
+                    exponent: 9,
+                    mantissa: 987654321
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiple_dots() {
+        for separator in SEPARATORS {
+            let (was_good, _) = parse_one(b"..987654321-----", 4, separator);
+
+            assert!(!was_good);
+        }
+    }
+
+    #[test]
+    fn test_invalid_separator() {
+        for separator in SEPARATORS {
+            let (was_good, _) = parse_one(b".9876_54321-----", 10, separator);
 
-    /// This is the actual length of the decimal. In the above example,
-    /// with the 16 bytes being "12345.234, rando",
-    /// the real length is 9. &"12345.234, rando"[..9] = "12345.234"
-    pub real_length: usize,
-}
+            assert!(!was_good);
+        }
+    }
 
-#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
-pub struct ParseOutput {
-    pub mantissa: u64,
-    pub exponent: u8,
-}
+    #[test]
+    #[allow(clippy::octal_escapes)]
+    fn test_zero_inside() {
+        for separator in SEPARATORS {
+            let (was_good, _) = parse_one(b".9876\054321-----", 10, separator);
 
-#[cfg(test)]
-mod test {
+            assert!(!was_good);
+        }
+    }
 
-    use super::*;
+    #[test]
+    fn test_dot_rejected_with_comma_separator() {
+        let input = ParseInput::new(b"123.45__________", 6).unwrap();
+        let mut output = [ParseOutput::default()];
+
+        let was_good = parse_decimals_with_separator::<1, false>(&[input], &mut output, b',');
+
+        assert!(!was_good);
+    }
 
     #[test]
-    fn test_zero() {
-        let data = [b'0'; 16];
-        for real_length in 1..16 {
-            let input = ParseInput {
-                data: &data,
-                real_length,
-            };
-            let mut output = [ParseOutput::default()];
+    fn test_separator_rejected() {
+        let input = ParseInput::new(b"1234____________", 4).unwrap();
+        let mut output = [ParseOutput::default()];
+
+        for separator in [b'2', b'0', b' ', b'+', b'-'] {
+            let was_good =
+                parse_decimals_with_separator::<1, false>(&[input], &mut output, separator);
+            assert!(!was_good, "{:?} accepted", separator as char);
+        }
+    }
 
-            let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+    fn parse_grouped(data: &[u8; 16], real_length: usize) -> Option<ParseOutput> {
+        let input = ParseInput::new(data, real_length).unwrap();
+        let mut output = [ParseOutput::default()];
+        parse_decimals_grouped::<1, false>(&[input], &mut output, b'.', b',').then_some(output[0])
+    }
 
-            assert!(was_good);
+    #[test]
+    fn test_grouped() {
+        let cases: [(&[u8; 16], usize, u64, u8); 6] = [
+            (b"1,234,567.89____", 12, 123456789, 2),
+            (b"999,999,999,999_", 15, 999999999999, 0),
+            (b"1,000.5_________", 7, 10005, 1),
+            (b"12,345__________", 6, 12345, 0),
+            (b"123456.7________", 8, 1234567, 1),
+            (b"123_____________", 3, 123, 0),
+        ];
+        for (data, real_length, mantissa, exponent) in cases {
             assert_eq!(
-                output[0],
-                ParseOutput {
-                    exponent: 0,
-                    mantissa: 0
-                }
+                parse_grouped(data, real_length),
+                Some(ParseOutput { mantissa, exponent }),
+                "{}",
+                std::str::from_utf8(&data[..real_length]).unwrap()
             );
         }
     }
 
     #[test]
-    fn test_a_big_decimal() {
-        let data = b"987654321.123_..";
-        let real_length = 13;
-        let input = ParseInput { data, real_length };
+    fn test_grouped_underscore_integer() {
+        let input = ParseInput::new(b"1_000_000_______", 9).unwrap();
         let mut output = [ParseOutput::default()];
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+        let was_good = parse_decimals_grouped::<1, true>(&[input], &mut output, b'.', b'_');
 
         assert!(was_good);
-        assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 3,
-                mantissa: 987654321123
-            }
-        );
+        assert_eq!(output[0].mantissa, 1000000);
     }
 
     #[test]
-    fn test_a_big_integer() {
-        let data = b"987654321123_..9";
-        let real_length = 12;
-        let input = ParseInput { data, real_length };
-        let mut output = [ParseOutput::default()];
+    fn test_bad_groups() {
+        let cases: [(&[u8; 16], usize); 8] = [
+            (b"1234,567________", 8),
+            (b",123____________", 4),
+            (b"1,23____________", 4),
+            (b"1,2345__________", 6),
+            (b"1,,234__________", 6),
+            (b"12,34,567_______", 9),
+            (b"1.234,567_______", 9),
+            (b"1,234,__________", 6),
+        ];
+        for (data, real_length) in cases {
+            assert_eq!(
+                parse_grouped(data, real_length),
+                None,
+                "{}",
+                std::str::from_utf8(&data[..real_length]).unwrap()
+            );
+        }
+    }
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+    #[test]
+    fn test_grouped_batch() {
+        let inputs = [
+            ParseInput::new(b"1,234.5_________", 7).unwrap(),
+            ParseInput::new(b"12.5____________", 4).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 2];
 
-        assert!(was_good);
-        assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 0,
-                mantissa: 987654321123
-            }
-        );
+        assert!(parse_decimals_grouped::<2, false>(
+            &inputs,
+            &mut outputs,
+            b'.',
+            b','
+        ));
+        assert_eq!(outputs[0].mantissa, 12345);
+        assert_eq!(outputs[1].mantissa, 125);
     }
 
     #[test]
-    fn test_full_sized_integer() {
-        let data = b"1234567898765432";
-        let real_length = 16;
-        let input = ParseInput { data, real_length };
+    fn test_grouped_comma_decimal() {
+        let input = ParseInput::new(b"1.234.567,89____", 12).unwrap();
         let mut output = [ParseOutput::default()];
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+        assert!(parse_decimals_grouped::<1, false>(
+            &[input],
+            &mut output,
+            b',',
+            b'.'
+        ));
+        assert_eq!(output[0].mantissa, 123456789);
+        assert_eq!(output[0].exponent, 2);
 
-        assert!(was_good);
-        assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 0,
-                mantissa: 1234567898765432
-            }
-        );
+        for (decimal_separator, group_separator) in [(b',', b','), (b'.', b'.'), (b'.', b' ')] {
+            assert!(!parse_decimals_grouped::<1, false>(
+                &[input],
+                &mut output,
+                decimal_separator,
+                group_separator
+            ));
+        }
+    }
+
+    fn parse_strict(data: &[u8; 16], real_length: usize) -> Option<ParseOutput> {
+        let input = ParseInput::new(data, real_length).unwrap();
+        let mut output = [ParseOutput::default()];
+        parse_decimals_strict::<1, false>(&[input], &mut output).then_some(output[0])
     }
 
     #[test]
-    fn test_max_integer() {
-        let data = b"9999999999999999";
-        let real_length = 16;
-        let input = ParseInput { data, real_length };
+    fn test_strict() {
+        let cases: [(&[u8; 16], usize, u64, u8); 6] = [
+            (b"0_______________", 1, 0, 0),
+            (b"0.5_____________", 3, 5, 1),
+            (b"0.000___________", 5, 0, 3),
+            (b"10.01___________", 5, 1001, 2),
+            (b"987654321.123_..", 13, 987654321123, 3),
+            (b"1234567898765432", 16, 1234567898765432, 0),
+        ];
+        for (data, real_length, mantissa, exponent) in cases {
+            assert_eq!(
+                parse_strict(data, real_length),
+                Some(ParseOutput { mantissa, exponent }),
+                "{}",
+                std::str::from_utf8(&data[..real_length]).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_strict_rejects() {
+        let cases: [(&[u8; 16], usize); 7] = [
+            (b"________________", 0),
+            (b".5______________", 2),
+            (b"5.______________", 2),
+            (b"007_____________", 3),
+            (b"00.5____________", 4),
+            (b"._______________", 1),
+            (b"1.2.3___________", 5),
+        ];
+        for (data, real_length) in cases {
+            assert_eq!(
+                parse_strict(data, real_length),
+                None,
+                "{:?}",
+                std::str::from_utf8(&data[..real_length]).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_strict_integers() {
+        let inputs = [
+            ParseInput::new(b"0_______________", 1).unwrap(),
+            ParseInput::new(b"120_____________", 3).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 2];
+        assert!(parse_decimals_strict::<2, true>(&inputs, &mut outputs));
+        assert_eq!(outputs[1].mantissa, 120);
+
+        let leading_zero = [ParseInput::new(b"012_____________", 3).unwrap()];
         let mut output = [ParseOutput::default()];
+        assert!(!parse_decimals_strict::<1, true>(
+            &leading_zero,
+            &mut output
+        ));
+    }
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+    #[test]
+    fn test_validate() {
+        let inputs = [
+            ParseInput::new(b"987654321.123_..", 13).unwrap(),
+            ParseInput::new(b"..987654321-----", 4).unwrap(),
+            ParseInput::new(b"9999999999999999", 16).unwrap(),
+            ParseInput::new(b".9876_54321-----", 10).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+        ];
+        let valid = validate_decimals(&inputs);
 
-        assert!(was_good);
+        assert_eq!(valid, 0b10101);
+        for (i, input) in inputs.iter().enumerate() {
+            let mut output = [ParseOutput::default()];
+            let was_good = parse_decimals::<1, false>(&[*input], &mut output);
+            assert_eq!(was_good, valid & (1 << i) != 0);
+        }
+    }
+
+    #[test]
+    fn test_implied() {
+        let inputs = [
+            ParseInput::new(b"0000012345678901", 16).unwrap(),
+            ParseInput::new(b"           12345", 16).unwrap(),
+            ParseInput::new(b"      __________", 6).unwrap(),
+            ParseInput::new(b"9999999999999999", 16).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 4];
+
+        assert!(parse_implied::<4, 2>(&inputs, &mut outputs));
+
+        let mantissas = outputs.map(|o| o.mantissa);
+        assert_eq!(mantissas, [12345678901, 12345, 0, 9999999999999999]);
+        assert!(outputs.iter().all(|o| o.exponent == 2));
+    }
+
+    #[test]
+    fn test_implied_rejects() {
+        for (data, real_length) in [
+            (b"  123 45________", 8),
+            (b"123  ___________", 5),
+            (b"  12.34_________", 7),
+            (b"  -1234_________", 7),
+        ] {
+            let input = ParseInput::new(data, real_length).unwrap();
+            let mut output = [ParseOutput::default()];
+            assert!(!parse_implied::<1, 2>(&[input], &mut output));
+        }
+    }
+
+    fn packed(bytes: &[u8]) -> [u8; 16] {
+        let mut data = [0xff; 16];
+        data[..bytes.len()].copy_from_slice(bytes);
+        data
+    }
+
+    #[test]
+    fn test_packed() {
+        let data = [
+            packed(&[0x12, 0x34, 0x56, 0x78, 0x90, 0x12, 0x34, 0x5c]),
+            packed(&[0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9d]),
+            packed(&[0x0f]),
+            packed(&[0x01, 0x2b]),
+            packed(&[0x00, 0x00, 0x1a]),
+            packed(&[0x5e]),
+        ];
+        let lengths = [8, 8, 1, 2, 3, 1];
+        let inputs: Vec<ParseInput> = data
+            .iter()
+            .zip(lengths)
+            .map(|(d, len)| ParseInput::new(d, len).unwrap())
+            .collect();
+        let mut outputs = [SignedParseOutput::default(); 6];
+
+        assert!(parse_packed_decimals::<6>(
+            inputs.as_slice().try_into().unwrap(),
+            &mut outputs,
+            3
+        ));
+
+        let mantissas = outputs.map(|o| o.mantissa);
+        assert_eq!(mantissas, [123456789012345, -999999999999999, 0, -12, 1, 5]);
+        assert!(outputs.iter().all(|o| o.exponent == 3));
+    }
+
+    #[test]
+    fn test_packed_rejects() {
+        for (bytes, real_length) in [
+            // Digit nibbles above 9
+            (packed(&[0x1a, 0x2c]), 2),
+            (packed(&[0xa1, 0x2c]), 2),
+            // Sign nibbles that are digits
+            (packed(&[0x12, 0x34]), 2),
+            (packed(&[0x12, 0x39]), 2),
+            // Nothing to hold the sign, or too long for one vector
+            (packed(&[]), 0),
+            (packed(&[0x00; 9]), 9),
+        ] {
+            let input = ParseInput::new(&bytes, real_length).unwrap();
+            let mut output = [SignedParseOutput::default()];
+            assert!(!parse_packed_decimals::<1>(&[input], &mut output, 0));
+        }
+    }
+
+    #[test]
+    fn test_zoned() {
+        let inputs = [
+            ParseInput::new(b"000000000012345{", 16).unwrap(),
+            ParseInput::new(b"999999999999999R", 16).unwrap(),
+            ParseInput::new(b"}_______________", 1).unwrap(),
+            ParseInput::new(b"00J_____________", 3).unwrap(),
+            ParseInput::new(b"42______________", 2).unwrap(),
+            ParseInput::new(b"7I______________", 2).unwrap(),
+        ];
+        let mut outputs = [SignedParseOutput::default(); 6];
+
+        assert!(parse_zoned_decimals::<6>(&inputs, &mut outputs, 4));
+
+        let mantissas = outputs.map(|o| o.mantissa);
+        assert_eq!(mantissas, [123450, -9999999999999999, 0, -1, 42, 79]);
+        assert!(outputs.iter().all(|o| o.exponent == 4));
+    }
+
+    #[test]
+    fn test_zoned_rejects() {
+        for (data, real_length) in [
+            (b"12S_____________", 3),
+            (b"1A2_____________", 3),
+            (b"12.5____________", 4),
+            (b" 12E____________", 4),
+            (b"________________", 0),
+        ] {
+            let input = ParseInput::new(data, real_length).unwrap();
+            let mut output = [SignedParseOutput::default()];
+            assert!(!parse_zoned_decimals::<1>(&[input], &mut output, 0));
+        }
+    }
+
+    fn ebcdic(digits: &[u8], last: u8) -> [u8; 16] {
+        let mut data = [0x40; 16];
+        for (byte, digit) in data.iter_mut().zip(digits) {
+            *byte = 0xF0 | digit;
+        }
+        data[digits.len()] = last;
+        data
+    }
+
+    #[test]
+    fn test_zoned_ebcdic() {
+        let data = [
+            ebcdic(&[0; 14], 0xC0),
+            ebcdic(&[9; 15], 0xD9),
+            ebcdic(&[], 0xD0),
+            ebcdic(&[0, 0], 0xD1),
+            ebcdic(&[4], 0xF2),
+            ebcdic(&[7], 0xC9),
+        ];
+        let lengths = [15, 16, 1, 3, 2, 2];
+        let inputs: [ParseInput; 6] =
+            std::array::from_fn(|i| ParseInput::new(&data[i], lengths[i]).unwrap());
+        let mut outputs = [SignedParseOutput::default(); 6];
+
+        assert!(parse_zoned_decimals_ebcdic::<6>(&inputs, &mut outputs, 4));
+
+        let mantissas = outputs.map(|o| o.mantissa);
+        assert_eq!(mantissas, [0, -9999999999999999, 0, -1, 42, 79]);
+        assert!(outputs.iter().all(|o| o.exponent == 4));
+    }
+
+    #[test]
+    fn test_zoned_ebcdic_rejects() {
+        for (data, real_length) in [
+            // The ASCII overpunch and digits
+            (*b"12E_____________", 3),
+            (ebcdic(&[1, 2], 0xE3), 3),
+            (ebcdic(&[1, 0xA], 0xC3), 3),
+            (ebcdic(&[1], 0x4B), 2),
+            (ebcdic(&[1, 2], 0xC3), 4),
+            (ebcdic(&[], 0xC0), 0),
+        ] {
+            let input = ParseInput::new(&data, real_length).unwrap();
+            let mut output = [SignedParseOutput::default()];
+            assert!(!parse_zoned_decimals_ebcdic::<1>(&[input], &mut output, 0));
+        }
+    }
+
+    fn utf16(s: &str) -> [u16; 16] {
+        let mut data = [0x2603; 16];
+        for (unit, c) in data.iter_mut().zip(s.encode_utf16()) {
+            *unit = c;
+        }
+        data
+    }
+
+    #[test]
+    fn test_utf16() {
+        let data = [
+            utf16("987654321.123"),
+            utf16("9999999999999999"),
+            utf16(""),
+            utf16(".5"),
+        ];
+        let inputs = [
+            ParseInput16::new(&data[0], 13).unwrap(),
+            ParseInput16::new(&data[1], 16).unwrap(),
+            ParseInput16::new(&data[2], 0).unwrap(),
+            ParseInput16::new(&data[3], 2).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 4];
+
+        // The non-ascii padding after each decimal is never looked at
+        assert!(parse_decimals_utf16::<4, false>(&inputs, &mut outputs));
         assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 0,
-                mantissa: 9999999999999999
-            }
+            outputs.map(|o| (o.mantissa, o.exponent)),
+            [(987654321123, 3), (9999999999999999, 0), (0, 0), (5, 1)]
         );
     }
 
     #[test]
-    fn test_min_decimal() {
-        let data = b".000000000000001";
-        let real_length = 16;
-        let input = ParseInput { data, real_length };
-        let mut output = [ParseOutput::default()];
+    fn test_utf16_rejects_wide_code_units() {
+        // Non-ascii digits, and code units whose low byte alone is a digit or dot
+        for wide in [0x0131, 0x012e, 0xff11, 0x8030, 0x00b9] {
+            let mut data = utf16("12345");
+            data[2] = wide;
+            let input = ParseInput16::new(&data, 5).unwrap();
+            let mut output = [ParseOutput::default()];
+            assert!(!parse_decimals_utf16::<1, false>(&[input], &mut output));
+        }
+    }
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+    #[test]
+    fn test_columnar_matches_parse() {
+        let inputs = [
+            ParseInput::new(b"987654321.123_..", 13).unwrap(),
+            ParseInput::new(b"9999999999999999", 16).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+            ParseInput::new(b".5______________", 2).unwrap(),
+            ParseInput::new(b"0.000000000001__", 14).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 5];
+        let mut mantissas = [0; 5];
+        let mut exponents = [0; 5];
 
-        assert!(was_good);
-        assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 15,
-                mantissa: 1
+        assert!(parse_decimals::<5, false>(&inputs, &mut outputs));
+        assert!(parse_decimals_columnar::<5, false>(
+            &inputs,
+            &mut mantissas,
+            &mut exponents
+        ));
+        assert_eq!(outputs.map(|o| o.mantissa), mantissas);
+        assert_eq!(outputs.map(|o| o.exponent), exponents);
+
+        // Stale exponents are cleared even though integers skip the dot search
+        let integers = [inputs[1], inputs[2]];
+        let mut mantissas = [0; 2];
+        let mut exponents = [7; 2];
+        assert!(parse_decimals_columnar::<2, true>(
+            &integers,
+            &mut mantissas,
+            &mut exponents
+        ));
+        assert_eq!(mantissas, [9999999999999999, 0]);
+        assert_eq!(exponents, [0, 0]);
+
+        let bad = [ParseInput::new(b"12x.45__________", 6).unwrap()];
+        assert!(!parse_decimals_columnar::<1, false>(
+            &bad,
+            &mut [0],
+            &mut [0]
+        ));
+    }
+
+    #[test]
+    fn test_integers() {
+        let inputs = [
+            ParseInput::new(b"0000000000000001", 16).unwrap(),
+            ParseInput::new(b"9999999999999999", 16).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+            ParseInput::new(b"123456789_______", 9).unwrap(),
+        ];
+        let mut outputs = [0; 4];
+        assert!(parse_u64s::<4>(&inputs, &mut outputs));
+        assert_eq!(outputs, [1, 9999999999999999, 0, 123456789]);
+
+        let dotted = [ParseInput::new(b"1.5_____________", 3).unwrap()];
+        assert!(!parse_u64s::<1>(&dotted, &mut [0]));
+        assert!(!parse_u32s::<1>(&dotted, &mut [0]));
+        assert!(!parse_i64s::<1>(&dotted, &mut [0]));
+    }
+
+    #[test]
+    fn test_u32_bounds() {
+        let mut outputs = [0; 3];
+
+        // Short enough to skip the last multiply
+        let short = [
+            ParseInput::new(b"99999999________", 8).unwrap(),
+            ParseInput::new(b"1_______________", 1).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+        ];
+        assert!(parse_u32s::<3>(&short, &mut outputs));
+        assert_eq!(outputs, [99999999, 1, 0]);
+
+        let long = [
+            ParseInput::new(b"4294967295______", 10).unwrap(),
+            ParseInput::new(b"0000000042______", 10).unwrap(),
+            ParseInput::new(b"123456789_______", 9).unwrap(),
+        ];
+        assert!(parse_u32s::<3>(&long, &mut outputs));
+        assert_eq!(outputs, [u32::MAX, 42, 123456789]);
+
+        for (data, len) in [
+            (b"4294967296______", 10),
+            (b"9999999999______", 10),
+            (b"00000000001_____", 11),
+        ] {
+            let input = ParseInput::new(data, len).unwrap();
+            assert!(!parse_u32s::<3>(&[long[1], input, long[2]], &mut outputs));
+        }
+    }
+
+    #[test]
+    fn test_i64s() {
+        let inputs = [
+            ParseInput::new(b"-999999999999999", 16).unwrap(),
+            ParseInput::new(b"9999999999999999", 16).unwrap(),
+            ParseInput::new(b"-0______________", 2).unwrap(),
+            ParseInput::new(b"-5-_____________", 2).unwrap(),
+        ];
+        let mut outputs = [0; 4];
+        assert!(parse_i64s::<4>(&inputs, &mut outputs));
+        assert_eq!(outputs, [-999999999999999, 9999999999999999, 0, -5]);
+
+        for (data, len) in [
+            (b"-_______________", 1),
+            (b"--5_____________", 3),
+            (b"5-______________", 2),
+        ] {
+            let input = ParseInput::new(data, len).unwrap();
+            assert!(!parse_i64s::<1>(&[input], &mut [0]));
+        }
+    }
+
+    #[test]
+    fn test_short_matches_parse() {
+        // Every string of up to 5 of these, padded out to 8 bytes with leading zeros and junk
+        let alphabet = b"09.-";
+        let mut checked = 0;
+        for length in 0..=5 {
+            for mut code in 0..alphabet.len().pow(length as u32) {
+                let mut data = [b'x'; 16];
+                for byte in data[..length].iter_mut() {
+                    *byte = alphabet[code % alphabet.len()];
+                    code /= alphabet.len();
+                }
+                for padded in [length, 8] {
+                    if padded > length {
+                        data.copy_within(..length, padded - length);
+                        data[..padded - length].fill(b'0');
+                    }
+                    let input = [ParseInput::new(&data, padded).unwrap()];
+
+                    let mut expected = [ParseOutput::default()];
+                    let mut actual = [ParseOutput::default()];
+                    let valid = parse_decimals::<1, false>(&input, &mut expected);
+                    assert_eq!(
+                        parse_short_decimals::<1, false>(&input, &mut actual),
+                        valid,
+                        "{:?}",
+                        &data[..padded]
+                    );
+                    if valid {
+                        assert_eq!(actual, expected, "{:?}", &data[..padded]);
+                    }
+                    checked += 1;
+                }
             }
+        }
+        assert!(checked > 1000);
+    }
+
+    #[test]
+    fn test_short_decimals() {
+        let inputs = [
+            ParseInput::new(b"99999999________", 8).unwrap(),
+            ParseInput::new(b"999999.9________", 8).unwrap(),
+            ParseInput::new(b".9999999________", 8).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+            ParseInput::new(b"12345678________", 8).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 5];
+        assert!(parse_short_decimals::<5, false>(&inputs, &mut outputs));
+        assert_eq!(
+            outputs.map(|o| (o.mantissa, o.exponent)),
+            [
+                (99999999, 0),
+                (9999999, 1),
+                (9999999, 7),
+                (0, 0),
+                (12345678, 0)
+            ]
         );
+
+        let mut integers = [ParseOutput::default(); 2];
+        assert!(parse_short_decimals::<2, true>(
+            &[inputs[0], inputs[4]],
+            &mut integers
+        ));
+        assert_eq!(integers.map(|o| o.mantissa), [99999999, 12345678]);
+        assert!(!parse_short_decimals::<1, true>(
+            &[inputs[1]],
+            &mut [ParseOutput::default()]
+        ));
+
+        let long = ParseInput::new(b"123456789_______", 9).unwrap();
+        assert!(!parse_short_decimals::<2, false>(
+            &[inputs[0], long],
+            &mut [ParseOutput::default(); 2]
+        ));
     }
 
     #[test]
-    fn test_dot_at_end() {
-        let data = b"987654321.------";
-        let real_length = 10;
-        let input = ParseInput { data, real_length };
-        let mut output = [ParseOutput::default()];
+    fn test_small_integers_match_u32s() {
+        // Every string of up to 4 of these in each lane, next to lanes of other lengths
+        let alphabet = b"0589.x";
+        let mut data = [[b'_'; 16]; 4];
+        for length in 0..=4 {
+            for mut code in 0..alphabet.len().pow(length as u32) {
+                for byte in data[1][..length].iter_mut() {
+                    *byte = alphabet[code % alphabet.len()];
+                    code /= alphabet.len();
+                }
+                data[0][..4].copy_from_slice(b"9999");
+                data[2][..1].copy_from_slice(b"0");
+                data[3][..3].copy_from_slice(b"123");
+                let inputs = [
+                    ParseInput::new(&data[0], 4).unwrap(),
+                    ParseInput::new(&data[1], length).unwrap(),
+                    ParseInput::new(&data[2], 1).unwrap(),
+                    ParseInput::new(&data[3], 3).unwrap(),
+                ];
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+                let mut expected = [0; 4];
+                let mut actual = [0; 4];
+                let valid = parse_u32s::<4>(&inputs, &mut expected);
+                assert_eq!(
+                    parse_small_integers::<4>(&inputs, &mut actual),
+                    valid,
+                    "{:?}",
+                    &data[1][..length]
+                );
+                if valid {
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
 
-        assert!(was_good);
-        assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 0,
-                mantissa: 987654321
+    #[test]
+    fn test_small_integers() {
+        let inputs = [
+            ParseInput::new(b"1_______________", 1).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+        ];
+        let mut outputs = [0; 2];
+        assert!(parse_small_integers::<2>(&inputs, &mut outputs));
+        assert_eq!(outputs, [1, 0]);
+
+        // Junk past the end of the input is never looked at, even within the first 4 bytes
+        let input = ParseInput::new(b"9.x-____________", 1).unwrap();
+        assert!(parse_small_integers::<1>(&[input], &mut [0]));
+
+        let long = ParseInput::new(b"12345___________", 5).unwrap();
+        assert!(!parse_small_integers::<2>(&[inputs[0], long], &mut outputs));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_small_integers_limit() {
+        let input = ParseInput::new(b"1_______________", 1).unwrap();
+        parse_small_integers::<5>(&[input; 5], &mut [0; 5]);
+    }
+
+    /// Sums by parsing each input and rescaling it, which is what sum_decimals replaces
+    fn reference_sum(inputs: &[ParseInput], scale: u8) -> Option<i128> {
+        inputs.iter().try_fold(0i128, |total, input| {
+            let mut output = [ParseOutput::default()];
+            if !parse_decimals::<1, false>(&[*input], &mut output) {
+                return None;
             }
-        );
+            let shift = scale.checked_sub(output[0].exponent)?;
+            let rescaled =
+                (output[0].mantissa as i128).checked_mul(10i128.checked_pow(shift as u32)?)?;
+            total.checked_add(rescaled)
+        })
     }
 
     #[test]
-    fn test_dot_at_start() {
-        let data = b".987654321------";
-        let real_length = 10;
-        let input = ParseInput { data, real_length };
-        let mut output = [ParseOutput::default()];
+    fn test_sum() {
+        let inputs = [
+            ParseInput::new(b"987654321.123_..", 13).unwrap(),
+            ParseInput::new(b"9999999999999999", 16).unwrap(),
+            ParseInput::new(b"0.001___________", 5).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+            ParseInput::new(b"12.5____________", 4).unwrap(),
+            ParseInput::new(b"999999999999999.", 16).unwrap(),
+        ];
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+        // Whether a lane is lined up in the vector or rescaled on its own
+        // depends on how many digits it has and how far it has to move
+        for scale in [3, 4, 8, 15, 16, 22] {
+            let mut total = 7;
+            assert!(sum_decimals::<6>(&inputs, scale, &mut total), "{}", scale);
+            assert_eq!(Some(total - 7), reference_sum(&inputs, scale), "{}", scale);
+        }
+    }
+
+    #[test]
+    fn test_sum_rejects() {
+        let inputs = [
+            ParseInput::new(b"1.25____________", 4).unwrap(),
+            ParseInput::new(b"3_______________", 1).unwrap(),
+        ];
+        let mut total = 0;
+
+        assert!(!sum_decimals::<2>(&inputs, 1, &mut total));
+        assert!(sum_decimals::<2>(&inputs, 2, &mut total));
+        assert_eq!(total, 425);
+
+        let bad = [ParseInput::new(b"1x5_____________", 3).unwrap()];
+        assert!(!sum_decimals::<1>(&bad, 2, &mut total));
+
+        // The batch fits in an i128, but not on top of a total that is nearly full
+        let big = [ParseInput::new(b"9999999999999999", 16).unwrap(); 2];
+        total = i128::MAX - 10i128.pow(37);
+        assert!(!sum_decimals::<2>(&big, 21, &mut total));
+        assert_eq!(total, i128::MAX - 10i128.pow(37));
+        assert!(!sum_decimals::<2>(&big, 40, &mut 0));
+    }
+
+    /// Compares by cross multiplying, which is what filter_decimals does with digits
+    fn reference_at_least(value: ParseOutput, threshold: ParseOutput) -> bool {
+        let scale = value.exponent.max(threshold.exponent);
+        let rescale = |o: ParseOutput| match o.mantissa {
+            0 => Some(0),
+            mantissa => 10u128
+                .checked_pow((scale - o.exponent) as u32)
+                .and_then(|power| (mantissa as u128).checked_mul(power)),
+        };
+        match (rescale(value), rescale(threshold)) {
+            (Some(value), Some(threshold)) => value >= threshold,
+            // The value is tiny next to a threshold that can't be rescaled
+            (Some(_), None) => false,
+            // But a value that can't be rescaled means the threshold is tiny
+            (None, _) => true,
+        }
+    }
+
+    #[test]
+    fn test_filter_against_reference() {
+        let data: [&[u8; 16]; 8] = [
+            b"987654321.123_..",
+            b"9999999999999999",
+            b".000000000000001",
+            b"________________",
+            b"12.5____________",
+            b"999999999999999.",
+            b"12.5000000000000",
+            b"1250____________",
+        ];
+        let lengths = [13, 16, 16, 0, 4, 16, 16, 4];
+        let inputs: [ParseInput; 8] =
+            std::array::from_fn(|i| ParseInput::new(data[i], lengths[i]).unwrap());
+
+        let thresholds = [
+            (0, 0),
+            (125, 1),
+            (1250, 2),
+            (12500000000000001, 16),
+            (1, 15),
+            (1, 16),
+            (1, 40),
+            (9999999999999999, 0),
+            (10000000000000000, 0),
+            (99999999999999991, 2),
+            (987654321123, 3),
+            (987654321124, 3),
+            (u64::MAX, 0),
+            (u64::MAX, 5),
+            (u64::MAX, 19),
+            (u64::MAX, 255),
+        ];
+
+        let mut outputs = [ParseOutput::default(); 8];
+        assert!(parse_decimals::<8, false>(&inputs, &mut outputs));
+
+        for (mantissa, exponent) in thresholds {
+            let threshold = ParseOutput { mantissa, exponent };
+            let expected = outputs
+                .iter()
+                .enumerate()
+                .map(|(i, o)| (reference_at_least(*o, threshold) as u32) << i)
+                .sum::<u32>();
+
+            let mut invalid = u32::MAX;
+            let at_least = filter_decimals::<8>(&inputs, threshold, &mut invalid);
+            assert_eq!(at_least, expected, "{:?}", threshold);
+            assert_eq!(invalid, 0);
+        }
+    }
+
+    #[test]
+    fn test_filter_invalid() {
+        let inputs = [
+            ParseInput::new(b"99x_____________", 3).unwrap(),
+            ParseInput::new(b"5_______________", 1).unwrap(),
+            ParseInput::new(b"1.2.3___________", 5).unwrap(),
+        ];
+        let threshold = ParseOutput {
+            mantissa: 1,
+            exponent: 0,
+        };
+        let mut invalid = 0;
 
-        assert!(was_good);
         assert_eq!(
-            output[0],
-            ParseOutput {
-                exponent: 9,
-                mantissa: 987654321
-            }
+            filter_decimals::<3>(&inputs, threshold, &mut invalid),
+            0b010
         );
+        assert_eq!(invalid, 0b101);
     }
 
     #[test]
-    fn test_multiple_dots() {
-        let data = b"..987654321-----";
-        let real_length = 4;
-        let input = ParseInput { data, real_length };
-        let mut output = [ParseOutput::default()];
+    fn test_rescaled_ties_and_carries() {
+        use RoundingMode::*;
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+        // (input, then its mantissa at four decimals when truncated, rounded half up and
+        // rounded half even). Every input has more than four decimals, so Reject always fails
+        let cases: [(&[u8], u64, u64, u64); 10] = [
+            (b"9.99995", 99999, 100000, 100000),
+            (b"9.99985", 99998, 99999, 99998),
+            (b"9.999949", 99999, 99999, 99999),
+            (b"0.00005", 0, 1, 0),
+            (b"0.00015", 1, 2, 2),
+            (b"0.000050000001", 0, 1, 1),
+            (b"0.000049999999", 0, 0, 0),
+            (
+                b"999999999.99999",
+                9999999999999,
+                10000000000000,
+                10000000000000,
+            ),
+            (b"123.456789", 1234567, 1234568, 1234568),
+            (b"123.45675", 1234567, 1234568, 1234568),
+        ];
 
-        assert!(!was_good);
+        for (text, truncated, half_up, half_even) in cases {
+            let mut data = [b'_'; 16];
+            data[..text.len()].copy_from_slice(text);
+            let input = [ParseInput::new(&data, text.len()).unwrap()];
+
+            for (mode, expected) in [
+                (Truncate, truncated),
+                (HalfUp, half_up),
+                (HalfEven, half_even),
+            ] {
+                let mut output = [ParseOutput::default()];
+                assert!(parse_decimals_rescaled::<1>(&input, &mut output, 4, mode));
+                assert_eq!(
+                    output[0],
+                    ParseOutput {
+                        mantissa: expected,
+                        exponent: 4
+                    },
+                    "{:?} {:?}",
+                    std::str::from_utf8(text),
+                    mode
+                );
+            }
+
+            let mut output = [ParseOutput::default()];
+            assert!(!parse_decimals_rescaled::<1>(
+                &input,
+                &mut output,
+                4,
+                Reject
+            ));
+        }
     }
 
     #[test]
-    fn test_invalid_separator() {
-        let data = b".9876_54321-----";
-        let real_length = 10;
-        let input = ParseInput { data, real_length };
+    fn test_rescaled_exact() {
+        let inputs = [
+            ParseInput::new(b"1.5_____________", 3).unwrap(),
+            ParseInput::new(b"1.2300__________", 6).unwrap(),
+            ParseInput::new(b"________________", 0).unwrap(),
+        ];
+        let mut outputs = [ParseOutput::default(); 3];
+
+        // Dropping only zeros is fine even when rejecting
+        assert!(parse_decimals_rescaled::<3>(
+            &inputs,
+            &mut outputs,
+            2,
+            RoundingMode::Reject
+        ));
+        assert_eq!(outputs.map(|o| o.mantissa), [150, 123, 0]);
+
+        // Adding decimals can overflow
+        let big = [ParseInput::new(b"9999999999999999", 16).unwrap()];
         let mut output = [ParseOutput::default()];
+        assert!(!parse_decimals_rescaled::<1>(
+            &big,
+            &mut output,
+            4,
+            RoundingMode::Truncate
+        ));
+    }
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+    /// Checks that the keys sort the same way as the values do
+    fn check_sort_keys(texts: &[&str; 8], scale: u8) -> bool {
+        let data: Vec<[u8; 16]> = texts
+            .iter()
+            .map(|text| {
+                let mut data = [b'_'; 16];
+                data[..text.len()].copy_from_slice(text.as_bytes());
+                data
+            })
+            .collect();
+        let inputs: [ParseInput; 8] =
+            std::array::from_fn(|i| ParseInput::new(&data[i], texts[i].len()).unwrap());
 
-        assert!(!was_good);
+        let mut keys = [0; 8];
+        if !sort_key_decimals::<8>(&inputs, &mut keys, scale) {
+            return false;
+        }
+
+        let mut outputs = [ParseOutput::default(); 8];
+        assert!(parse_decimals::<8, false>(&inputs, &mut outputs));
+        for i in 0..8 {
+            for j in 0..8 {
+                let expected = outputs[i].numeric_cmp(&outputs[j]);
+                assert_eq!(keys[i].cmp(&keys[j]), expected, "{} {} {}", scale, i, j);
+            }
+        }
+        true
     }
 
     #[test]
-    #[allow(clippy::octal_escapes)]
-    fn test_zero_inside() {
-        let data = b".9876\054321-----";
-        let real_length = 10;
-        let input = ParseInput { data, real_length };
-        let mut output = [ParseOutput::default()];
+    fn test_sort_keys() {
+        let short = [
+            "987654321.123",
+            "0.001",
+            "",
+            "12.5",
+            "999999999999.",
+            "12.500",
+            "0000000000000012",
+            "0",
+        ];
+        for scale in [3, 4, 6] {
+            assert!(check_sort_keys(&short, scale), "{}", scale);
+        }
+        // Too few decimals for 0.001, and too many for 999999999999 to fit in an i64
+        assert!(!check_sort_keys(&short, 2));
+        assert!(!check_sort_keys(&short, 7));
 
-        let was_good = unsafe { do_parse_decimals::<1, false>(&[input], &mut output) };
+        // Past the most decimals that fit in a vector, every lane is rescaled on its own
+        let long = [
+            "87.123",
+            "9.99999999999999",
+            "0.001",
+            "",
+            "12.5",
+            "91.",
+            "12.500",
+            ".000000000000001",
+        ];
+        for scale in [15, 16, 17] {
+            assert!(check_sort_keys(&long, scale), "{}", scale);
+        }
+        assert!(!check_sort_keys(&long, 18));
+    }
 
-        assert!(!was_good);
+    #[test]
+    fn test_signed_sort_keys() {
+        let signed = |mantissa, exponent| SignedParseOutput { mantissa, exponent };
+        let ascending = [
+            signed(i64::MIN / 100, 0),
+            signed(-1000, 0),
+            signed(-15, 1),
+            signed(-149, 2),
+            signed(0, 0),
+            signed(1, 2),
+            signed(15, 1),
+            signed(i64::MAX / 100, 0),
+        ];
+
+        let keys: Vec<u64> = ascending.iter().map(|v| v.sort_key(2).unwrap()).collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // The keys from parsing line up with the signed ones
+        let input = [ParseInput::new(b"1.5_____________", 3).unwrap()];
+        let mut key = [0];
+        assert!(sort_key_decimals::<1>(&input, &mut key, 2));
+        assert_eq!(key[0], keys[6]);
+
+        assert_eq!(signed(1, 3).sort_key(2), None);
+        assert_eq!(signed(i64::MIN / 10, 0).sort_key(2), None);
     }
 }
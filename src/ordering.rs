@@ -0,0 +1,222 @@
+//! Compares decimals by value rather than by representation.
+//!
+//! The derived `PartialEq` on [`ParseOutput`] compares mantissas and exponents, so "1.50" and
+//! "1.5" are different outputs. This adds numeric comparisons, [`NormalizedDecimal`]
+//! for when the values need to be map keys, and sort keys for radix sorting.
+
+use std::cmp::Ordering;
+
+use crate::{ParseOutput, SignedParseOutput};
+
+impl ParseOutput {
+    /// Removes trailing zeros from the mantissa, so that every value has exactly one
+    /// representation. Zero always ends up with an exponent of 0
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// use simd_decimal::ParseOutput;
+    ///
+    /// let price = ParseOutput { mantissa: 150, exponent: 2 };
+    /// assert_eq!(price.normalize(), ParseOutput { mantissa: 15, exponent: 1 });
+    /// ```
+    #[inline]
+    pub fn normalize(self) -> ParseOutput {
+        let ParseOutput {
+            mut mantissa,
+            mut exponent,
+        } = self;
+
+        if mantissa == 0 {
+            return ParseOutput::default();
+        }
+        while exponent > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent -= 1;
+        }
+        ParseOutput { mantissa, exponent }
+    }
+
+    /// Compares by value, lining up the exponents first
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// use simd_decimal::ParseOutput;
+    /// use std::cmp::Ordering;
+    ///
+    /// let a = ParseOutput { mantissa: 15, exponent: 1 };
+    /// let b = ParseOutput { mantissa: 149, exponent: 2 };
+    /// assert_eq!(a.numeric_cmp(&b), Ordering::Greater);
+    /// ```
+    #[inline]
+    pub fn numeric_cmp(&self, other: &ParseOutput) -> Ordering {
+        let (a, b) = (self.normalize(), other.normalize());
+
+        // The one with fewer decimals gets scaled up. A u64 times 10^19 still fits in a u128,
+        // and anything past that which isn't zero is bigger than any u64
+        let scale_up = |m: u64, by: u8| (by <= 19).then(|| m as u128 * 10u128.pow(by as u32));
+
+        match a.exponent.cmp(&b.exponent) {
+            Ordering::Equal => a.mantissa.cmp(&b.mantissa),
+            Ordering::Less => match scale_up(a.mantissa, b.exponent - a.exponent) {
+                Some(a_mantissa) => a_mantissa.cmp(&(b.mantissa as u128)),
+                None if a.mantissa == 0 => 0.cmp(&b.mantissa),
+                None => Ordering::Greater,
+            },
+            Ordering::Greater => b.numeric_cmp(&a).reverse(),
+        }
+    }
+
+    /// Whether the two are the same value, so "1.50" and "1.5" are equal
+    #[inline]
+    pub fn numeric_eq(&self, other: &ParseOutput) -> bool {
+        self.normalize() == other.normalize()
+    }
+}
+
+impl SignedParseOutput {
+    /// Turns the value into a key whose unsigned order is the same as the numeric order,
+    /// for values with at most scale decimals. These are the same keys as
+    /// [`crate::sort_key_decimals`] makes, so the two can be sorted together.
+    ///
+    /// Returns None if the value has more than scale decimals, or doesn't fit in an i64 at scale
+    ///
+    /// Examples:
+    ///
+    /// ```
+    /// use simd_decimal::SignedParseOutput;
+    ///
+    /// let a = SignedParseOutput { mantissa: -15, exponent: 1 };
+    /// let b = SignedParseOutput { mantissa: -149, exponent: 2 };
+    /// assert!(a.sort_key(2).unwrap() < b.sort_key(2).unwrap());
+    /// ```
+    #[inline]
+    pub fn sort_key(self, scale: u8) -> Option<u64> {
+        let shift = scale.checked_sub(self.exponent)?;
+        let value = match self.mantissa {
+            0 => 0,
+            mantissa => mantissa.checked_mul(10i64.checked_pow(shift as u32)?)?,
+        };
+        Some(sort_key(value))
+    }
+}
+
+/// Flips the sign bit, which moves the negatives below the positives
+/// and leaves both in order as unsigned integers
+#[inline(always)]
+pub(crate) fn sort_key(value: i64) -> u64 {
+    value as u64 ^ (1 << 63)
+}
+
+/// A [`ParseOutput`] which is always normalized, so that equality, hashing and ordering
+/// all go by value. This is what to use for keys of a `BTreeMap` or `HashMap`.
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::{NormalizedDecimal, ParseOutput};
+/// use std::collections::BTreeMap;
+///
+/// let mut levels = BTreeMap::new();
+/// levels.insert(NormalizedDecimal::new(ParseOutput { mantissa: 150, exponent: 2 }), 3);
+/// levels.insert(NormalizedDecimal::new(ParseOutput { mantissa: 149, exponent: 2 }), 1);
+///
+/// // 1.5 is the same level as 1.50
+/// assert_eq!(levels[&NormalizedDecimal::new(ParseOutput { mantissa: 15, exponent: 1 })], 3);
+/// assert_eq!(levels.keys().next().unwrap().get().mantissa, 149);
+/// ```
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy, Hash)]
+pub struct NormalizedDecimal(ParseOutput);
+
+impl NormalizedDecimal {
+    #[inline]
+    pub fn new(output: ParseOutput) -> Self {
+        NormalizedDecimal(output.normalize())
+    }
+
+    #[inline]
+    pub fn get(self) -> ParseOutput {
+        self.0
+    }
+}
+
+impl From<ParseOutput> for NormalizedDecimal {
+    #[inline]
+    fn from(output: ParseOutput) -> Self {
+        NormalizedDecimal::new(output)
+    }
+}
+
+impl Ord for NormalizedDecimal {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.numeric_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for NormalizedDecimal {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(mantissa: u64, exponent: u8) -> ParseOutput {
+        ParseOutput { mantissa, exponent }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(output(1500, 3).normalize(), output(15, 1));
+        assert_eq!(output(1500, 0).normalize(), output(1500, 0));
+        assert_eq!(output(0, 9).normalize(), output(0, 0));
+        assert_eq!(output(10, 1).normalize(), output(1, 0));
+        assert_eq!(output(u64::MAX, 255).normalize(), output(u64::MAX, 255));
+    }
+
+    #[test]
+    fn test_numeric_cmp() {
+        let ascending = [
+            output(0, 200),
+            output(1, 255),
+            output(1, 20),
+            output(1, 19),
+            output(999, 3),
+            output(1, 0),
+            output(100, 2),
+            output(1001, 3),
+            output(u64::MAX, 19),
+            output(u64::MAX, 0),
+        ];
+
+        for (i, a) in ascending.iter().enumerate() {
+            for (j, b) in ascending.iter().enumerate() {
+                let expected = match (i, j) {
+                    // 1 and 1.00 are the same value
+                    (5, 6) | (6, 5) => Ordering::Equal,
+                    _ => i.cmp(&j),
+                };
+                assert_eq!(a.numeric_cmp(b), expected, "{:?} {:?}", a, b);
+                assert_eq!(a.numeric_eq(b), expected == Ordering::Equal);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalized_keys() {
+        use std::collections::HashSet;
+
+        let keys: HashSet<NormalizedDecimal> = [output(15, 1), output(150, 2), output(1500, 3)]
+            .into_iter()
+            .map(NormalizedDecimal::from)
+            .collect();
+
+        assert_eq!(keys.len(), 1);
+        assert!(NormalizedDecimal::new(output(0, 5)) < NormalizedDecimal::new(output(1, 255)));
+    }
+}
@@ -0,0 +1,16 @@
+//! Scalar helper for fixed-width fields padded with spaces on the left.
+//! Like the grouping checks, this works on a comparison bitmask of the right-aligned number
+
+use crate::DecimalLen;
+
+/// Returns true if the spaces are all at the front of the field, before any digits.
+/// space_mask has bit i set if byte i of the vector is a space.
+/// A field of only spaces is valid, and parses as zero
+#[inline(always)]
+pub(crate) fn left_padding_is_valid(space_mask: u32, real_length: DecimalLen) -> bool {
+    let start = 16 - real_length.get() as u32;
+
+    // The run of spaces has to start right at the front, so this is some number of low ones
+    let run = space_mask >> start;
+    run & run.wrapping_add(1) == 0
+}
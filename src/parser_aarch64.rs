@@ -1,13 +1,31 @@
 use std::arch::aarch64::{
-    vaddvq_u64, vceqq_u8, vcgeq_u8, vdupq_n_u8, vget_lane_u64, vget_low_u16, vget_low_u32,
-    vget_low_u8, vgetq_lane_u64, vmlal_high_n_u16, vmlal_high_n_u32, vmlal_high_u8, vmovl_u16,
-    vmovl_u32, vmovl_u8, vorrq_u8, vqtbl1q_u8, vreinterpret_u64_u8, vreinterpretq_u16_u8,
+    uint16x8_t, uint32x4_t, uint8x16_t, vaddv_u8, vaddvq_u64, vandq_u8, vbicq_u8, vbslq_u8,
+    vceqq_u8, vcgeq_u8, vcgtq_u8, vcltq_u8, vcombine_u8, vdupq_n_u16, vdupq_n_u32, vdupq_n_u8,
+    vextq_u8, vget_high_u8, vget_lane_u64, vget_low_u16, vget_low_u32, vget_low_u8, vgetq_lane_u64,
+    vgetq_lane_u8, vminvq_u8, vmlal_high_n_u16, vmlal_high_n_u32, vmlal_high_u8, vmovl_u16,
+    vmovl_u32, vmovl_u8, vmull_high_u8, vmull_u8, vmulq_u16, vorrq_u8, vpaddlq_u16, vpaddq_u16,
+    vqmovn_u16, vqtbl1q_u8, vreinterpret_u64_u8, vreinterpretq_u16_u32, vreinterpretq_u16_u8,
     vreinterpretq_u32_u8, vreinterpretq_u64_u8, vreinterpretq_u8_u16, vreinterpretq_u8_u32,
-    vreinterpretq_u8_u64, vshrn_n_u16, vsubq_u8,
+    vreinterpretq_u8_u64, vshrn_n_u16, vshrq_n_u8, vsubq_u8,
 };
 
-use crate::tables::{VecCharArray, DOT_SHUFFLE_CONTROL, EXPONENT_FROM_BITS, LENGTH_SHIFT_CONTROL};
-use crate::{ParseInput, ParseOutput};
+use crate::cobol::{apply_signs, packed_sign_is_negative, remove_overpunch, MAX_PACKED_BYTES};
+use crate::columns::{OutputColumns, SplitOutputs};
+use crate::filter::{digits_at_least, Alignment, Threshold};
+use crate::grouping::{group_shuffle_index, grouping_is_valid};
+use crate::integers::{
+    remove_minus_sign, u32_from_halves, HALF_DIGITS, MAX_SMALL_LENGTH, MAX_U32_DIGITS,
+    SMALL_LANE_OFFSET, SMALL_PER_VECTOR,
+};
+use crate::ordering::sort_key;
+use crate::padding::left_padding_is_valid;
+use crate::strict::strict_grammar_is_valid;
+use crate::sum::{mantissa_at_scale, scale_shift, sum_at_scale, MAX_VECTOR_SCALE};
+use crate::tables::{
+    VecCharArray, DOT_SHUFFLE_CONTROL, EXPONENT_FROM_BITS, GROUP_SHUFFLE_CONTROL,
+    LENGTH_SHIFT_CONTROL, PACKED_SHUFFLE_CONTROL, SCALE_SHIFT_CONTROL, SMALL_SHIFT_CONTROL,
+};
+use crate::{DecimalLen, ParseInput, ParseInput16, ParseOutput, SignedParseOutput};
 
 // base_1 conversion back and forth
 const fn a(idx: u8) -> u8 {
@@ -43,79 +61,849 @@ const SHUFFLE_ACC: VecCharArray<1> = VecCharArray {
     ]],
 };
 
+// Selects the high nibble lanes of a packed decimal after the shuffle
+const HIGH_NIBBLE_LANES: VecCharArray<1> = VecCharArray {
+    chars: [[
+        0xff, 0, 0xff, 0, 0xff, 0, 0xff, 0, 0xff, 0, 0xff, 0, 0xff, 0, 0xff, 0,
+    ]],
+};
+
+// Weights to turn a comparison mask into one bit per byte, with one horizontal add per half
+const MOVEMASK_BITS: VecCharArray<1> = VecCharArray {
+    chars: [[1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128]],
+};
+
 // aarch64 version of the sse parser. Most documentation is there.
 
 /// Parses the inputs passed into (mantissa, exponent) pairs.
 /// If any of them detected invalid, returns false
 /// # Safety
 ///
-/// It is unsafe to pass anything with a real_length that is greater than 16
+/// This is only unsafe because of the neon intrinsics, which are always present on aarch64.
+/// Lengths are bounded by [`crate::DecimalLen`] so the table lookups are always in range
+#[inline]
 pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     inputs: &[ParseInput; N],
     outputs: &mut [ParseOutput; N],
 ) -> bool {
+    do_parse_decimals_with_separator::<N, KNOWN_INTEGER>(inputs, outputs, b'.')
+}
+
+/// Parses the inputs like [`do_parse_decimals`], with decimal_separator in place of the dot
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_decimals_with_separator<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    decimal_separator: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, decimal_separator);
+    }
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    true
+}
+
+/// Parses utf-16 inputs like [`do_parse_decimals`]
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_decimals_utf16<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput16; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    let mut cleaned = load_and_shift_utf16(inputs);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, b'.');
+    }
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    true
+}
+
+/// Parses the inputs like [`do_parse_decimals`], writing the mantissas and exponents
+/// into separate arrays
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_decimals_columnar<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    mantissas: &mut [u64; N],
+    exponents: &mut [u8; N],
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+    let mut outputs = SplitOutputs {
+        mantissas,
+        exponents,
+    };
+
+    if KNOWN_INTEGER {
+        outputs.set_exponents([0; N]);
+    } else {
+        remove_dots(&mut cleaned, &mut outputs, b'.');
+    }
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, &mut outputs);
+
+    true
+}
+
+/// Parses the inputs as plain unsigned integers
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_u64s<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u64; N],
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    true
+}
+
+/// Parses the inputs as plain unsigned integers, and returns false if any have more than
+/// ten digits or don't fit in a u32
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_u32s<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u32; N],
+) -> bool {
+    let longest = inputs
+        .iter()
+        .map(|input| input.real_length.get())
+        .max()
+        .unwrap_or(0);
+    if longest > MAX_U32_DIGITS {
+        return false;
+    }
+
+    let mut cleaned = load_and_shift(inputs);
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    reduce_to_halves(&mut cleaned);
+
+    // When nothing reaches the top eight digits the last multiply can be skipped entirely
+    if longest <= HALF_DIGITS {
+        for i in 0..N {
+            outputs[i] = vgetq_lane_u64(vreinterpretq_u64_u8(cleaned[i]), 0) as u32;
+        }
+        return true;
+    }
+
+    let mut fits = true;
+    for i in 0..N {
+        let halves = vreinterpretq_u64_u8(cleaned[i]);
+        let combined = u32_from_halves(
+            vgetq_lane_u64(halves, 1) as u32,
+            vgetq_lane_u64(halves, 0) as u32,
+        );
+        fits &= combined.is_some();
+        outputs[i] = combined.unwrap_or(0);
+    }
+
+    fits
+}
+
+/// Parses the inputs as plain integers with an optional leading minus sign
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_i64s<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [i64; N],
+) -> bool {
+    let mut data = [[0; 16]; N];
+    let mut negative = [false; N];
+    for i in 0..N {
+        data[i] = *inputs[i].data;
+        negative[i] = remove_minus_sign(&mut data[i], inputs[i].real_length.get());
+    }
+
+    let unsigned_inputs: [ParseInput; N] = std::array::from_fn(|i| ParseInput {
+        data: &data[i],
+        real_length: inputs[i].real_length,
+    });
+    let mut cleaned = load_and_shift(&unsigned_inputs);
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    let mut unsigned = [0u64; N];
+    reduce_to_mantissas(&mut cleaned, &mut unsigned);
+
+    for i in 0..N {
+        let magnitude = unsigned[i] as i64;
+        outputs[i] = if negative[i] { -magnitude } else { magnitude };
+    }
+
+    true
+}
+
+/// Parses up to four integers of at most 4 bytes, all gathered into a single vector.
+/// Returns false if any are invalid or too long
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]. Panics if N is more than [`SMALL_PER_VECTOR`]
+pub unsafe fn do_parse_small_integers<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u32; N],
+) -> bool {
+    let mut words = [0; SMALL_PER_VECTOR];
+    let mut controls = [SMALL_SHIFT_CONTROL[0]; SMALL_PER_VECTOR];
+    for i in 0..N {
+        let length = inputs[i].real_length.get();
+        if length > MAX_SMALL_LENGTH {
+            return false;
+        }
+
+        let mut bytes = [0; MAX_SMALL_LENGTH];
+        bytes.copy_from_slice(&inputs[i].data[..MAX_SMALL_LENGTH]);
+        words[i] = u32::from_le_bytes(bytes);
+        controls[i] = SMALL_SHIFT_CONTROL[length] + SMALL_LANE_OFFSET * i as u32;
+    }
+
+    let loaded: uint8x16_t = std::mem::transmute(words);
+    let control: uint8x16_t = std::mem::transmute(controls);
+    let ascii = vdupq_n_u8(b'0');
+    let cleaned = [vqtbl1q_u8(vsubq_u8(loaded, ascii), control)];
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    let values: [u32; SMALL_PER_VECTOR] = std::mem::transmute(reduce_to_quads(&cleaned)[0]);
+    outputs.copy_from_slice(&values[..N]);
+
+    true
+}
+
+/// Parses the inputs like [`do_parse_decimals`] and adds them up at the given scale.
+/// Returns None if any are invalid, have more decimals than scale, or the sum overflows
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_sum_decimals<const N: usize>(inputs: &[ParseInput; N], scale: u8) -> Option<i128> {
+    let mut cleaned = load_and_shift(inputs);
+
+    let mut parsed = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut parsed, b'.');
+
+    let vector_scale = scale.min(MAX_VECTOR_SCALE);
+    let aligned = align_to_scale(&mut cleaned, &parsed, vector_scale);
+
+    if !check_digits(&cleaned) {
+        return None;
+    }
+
+    reduce_to_mantissas(&mut cleaned, &mut parsed);
+
+    sum_at_scale(&parsed, &aligned, vector_scale, scale)
+}
+
+/// Checks the inputs like [`do_validate_decimals`], and compares them against threshold.
+/// Returns a mask of the valid inputs which are at least threshold, and the mask of valid inputs
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_filter_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    threshold: ParseOutput,
+) -> (u32, u32) {
+    let threshold = Threshold::new(threshold);
+    let threshold_digits: uint8x16_t = std::mem::transmute(threshold.digits);
+
+    let mut cleaned = load_and_shift(inputs);
+
+    let mut parsed = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut parsed, b'.');
+
+    let valid = digit_lanes(&cleaned);
+
+    let zero = vdupq_n_u8(0);
+    let mut at_least = 0;
+    for i in 0..N {
+        let zero_mask = movemask(vceqq_u8(cleaned[i], zero));
+
+        let lane_at_least = match threshold.align(zero_mask, parsed[i].exponent) {
+            Alignment::Known(answer) => answer,
+            Alignment::Shift {
+                lane,
+                threshold: threshold_shift,
+            } => {
+                let lane_control = SCALE_SHIFT_CONTROL.vecs.get_unchecked(lane as usize);
+                let threshold_control = SCALE_SHIFT_CONTROL
+                    .vecs
+                    .get_unchecked(threshold_shift as usize);
+                let lane = vqtbl1q_u8(cleaned[i], *lane_control);
+                let threshold = vqtbl1q_u8(threshold_digits, *threshold_control);
+
+                let greater = movemask(vcgtq_u8(lane, threshold));
+                let less = movemask(vcgtq_u8(threshold, lane));
+                digits_at_least(greater, less)
+            }
+        };
+
+        at_least |= (lane_at_least as u32) << i;
+    }
+
+    (at_least & valid, valid)
+}
+
+/// Parses the inputs like [`do_parse_decimals`] into keys which sort the same way
+/// as the values do at the given scale. Returns false if any are invalid,
+/// have more decimals than scale, or don't fit in an i64 at scale
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_sort_key_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    keys: &mut [u64; N],
+    scale: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    let mut parsed = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut parsed, b'.');
+
+    // The same alignment as summing, so most lanes come out of the reduction already at scale
+    let vector_scale = scale.min(MAX_VECTOR_SCALE);
+    let aligned = align_to_scale(&mut cleaned, &parsed, vector_scale);
+
+    if !check_digits(&cleaned) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, &mut parsed);
+
+    let mut keys_good = true;
+    for i in 0..N {
+        let mantissa = mantissa_at_scale(parsed[i], aligned[i], vector_scale, scale)
+            .and_then(|mantissa| i64::try_from(mantissa).ok());
+        keys_good &= mantissa.is_some();
+        keys[i] = sort_key(mantissa.unwrap_or(0));
+    }
+
+    keys_good
+}
+
+/// Checks the inputs like [`do_parse_decimals`] without computing mantissas.
+/// Returns a mask with bit i set if input i is valid
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_validate_decimals<const N: usize>(inputs: &[ParseInput; N]) -> u32 {
+    let mut cleaned = load_and_shift(inputs);
+
+    // The exponents are never read, so these stores get optimized out
+    let mut exponents = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut exponents, b'.');
+
+    digit_lanes(&cleaned)
+}
+
+/// Parses the inputs as fixed-width integers which may be padded on the left with spaces,
+/// and gives them all the same exponent
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_implied<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    exponent: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    let padding_good = remove_left_padding(&mut cleaned, inputs);
+
+    if !(check_digits(&cleaned) & padding_good) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    for output in outputs.iter_mut() {
+        output.exponent = exponent;
+    }
+
+    true
+}
+
+/// Parses the inputs like [`do_parse_decimals`], but rejects anything outside of
+/// the JSON number grammar
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_decimals_strict<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    let grammar_good = check_strict_grammar::<N, KNOWN_INTEGER>(&cleaned, inputs);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, b'.');
+    }
+
+    if !(check_digits(&cleaned) & grammar_good) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    true
+}
+
+/// Parses the inputs like [`do_parse_decimals_with_separator`], but first removes thousands separators
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_decimals_grouped<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    decimal_separator: u8,
+    group_separator: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    let groups_good =
+        remove_groups::<N, KNOWN_INTEGER>(&mut cleaned, inputs, decimal_separator, group_separator);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, decimal_separator);
+    }
+
+    if !(check_digits(&cleaned) & groups_good) {
+        return false;
+    }
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    true
+}
+
+/// Parses the inputs as packed decimals, where real_length is the number of bytes
+/// and the sign is in the last nibble. Every output gets the same exponent
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_packed_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    exponent: u8,
+) -> bool {
+    let mut cleaned = [vdupq_n_u8(0); N];
+    let mut signs = [0; N];
+    let mut lengths_good = true;
+
+    for i in 0..N {
+        let length = inputs[i].real_length.get();
+        lengths_good &= (1..=MAX_PACKED_BYTES).contains(&length);
+
+        let loaded = std::mem::transmute::<[u8; 16], uint8x16_t>(*inputs[i].data);
+        let packed_control = PACKED_SHUFFLE_CONTROL
+            .vecs
+            .get_unchecked(length.min(MAX_PACKED_BYTES));
+
+        cleaned[i] = vqtbl1q_u8(loaded, *packed_control);
+    }
+
+    // Unlike sse there is a real 8 bit shift, so the nibbles can just be selected
+    let low_nibble = vdupq_n_u8(0x0f);
+    let high_lanes = HIGH_NIBBLE_LANES.vecs[0];
+    for cl in cleaned.iter_mut() {
+        let high = vshrq_n_u8(*cl, 4);
+        let low = vandq_u8(*cl, low_nibble);
+        *cl = vbslq_u8(high_lanes, high, low);
+    }
+
+    let zero = vdupq_n_u8(0);
+    for i in 0..N {
+        signs[i] = vgetq_lane_u8(cleaned[i], 15);
+        cleaned[i] = vextq_u8(zero, cleaned[i], 15);
+    }
+
+    let mut negative = [false; N];
+    let mut signs_good = true;
+    for i in 0..N {
+        let sign = packed_sign_is_negative(signs[i]);
+        signs_good &= sign.is_some();
+        negative[i] = sign == Some(true);
+    }
+
+    if !(check_digits(&cleaned) & lengths_good & signs_good) {
+        return false;
+    }
+
+    let mut unsigned = [ParseOutput::default(); N];
+    reduce_to_mantissas(&mut cleaned, &mut unsigned);
+
+    apply_signs(&unsigned, &negative, exponent, outputs);
+
+    true
+}
+
+/// Parses the inputs as zoned decimals, where the sign is overpunched onto the last digit.
+/// They are EBCDIC if that's set and ASCII otherwise. Every output gets the same exponent
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+pub unsafe fn do_parse_zoned_decimals<const N: usize, const EBCDIC: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    exponent: u8,
+) -> bool {
+    let mut data = [[0; 16]; N];
+    let mut negative = [false; N];
+    let mut lengths_good = true;
+    for i in 0..N {
+        data[i] = *inputs[i].data;
+        let sign = remove_overpunch::<EBCDIC>(&mut data[i], inputs[i].real_length.get());
+        lengths_good &= sign.is_some();
+        negative[i] = sign == Some(true);
+    }
+
+    let unpunched: [ParseInput; N] = std::array::from_fn(|i| ParseInput {
+        data: &data[i],
+        real_length: inputs[i].real_length,
+    });
+    let mut cleaned = load_and_shift(&unpunched);
+
+    if !(check_digits(&cleaned) & lengths_good) {
+        return false;
+    }
+
+    let mut unsigned = [ParseOutput::default(); N];
+    reduce_to_mantissas(&mut cleaned, &mut unsigned);
+
+    apply_signs(&unsigned, &negative, exponent, outputs);
+
+    true
+}
+
+/// Compares a chunk against each of the bytes, and returns a mask per byte
+/// with bit i set if chunk[i] is equal to it. This is used to find structure, not parse
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn find_byte_masks<const M: usize>(chunk: &[u8; 16], bytes: [u8; M]) -> [u32; M] {
+    let loaded: uint8x16_t = std::mem::transmute(*chunk);
+    let mut masks = [0; M];
+    for i in 0..M {
+        masks[i] = movemask(vceqq_u8(loaded, vdupq_n_u8(bytes[i])));
+    }
+    masks
+}
+
+#[inline(always)]
+unsafe fn load_and_shift<const N: usize>(inputs: &[ParseInput; N]) -> [uint8x16_t; N] {
     let ascii = vdupq_n_u8(b'0');
-    let dot = vdupq_n_u8((b'.').wrapping_sub(b'0'));
     let mut cleaned = [vdupq_n_u8(0); N];
 
     for i in 0..N {
         // transumte will just compile to the intrinsics anyways
-        let loaded = std::mem::transmute(*inputs[i].data);
+        let loaded = std::mem::transmute::<[u8; 16], uint8x16_t>(*inputs[i].data);
         cleaned[i] = vsubq_u8(loaded, ascii);
     }
 
+    shift_to_length(&mut cleaned, &inputs.map(|input| input.real_length));
+
+    cleaned
+}
+
+/// Loads utf-16 inputs and narrows them to bytes, then cleans them like load_and_shift
+#[inline(always)]
+unsafe fn load_and_shift_utf16<const N: usize>(inputs: &[ParseInput16; N]) -> [uint8x16_t; N] {
+    let ascii = vdupq_n_u8(b'0');
+    let mut cleaned = [vdupq_n_u8(0); N];
+
+    // The plain vmovn truncates, which would turn 0x131 into '1', so this uses the saturating one.
+    // Nothing from 0x80 to 0xff is a digit or a dot, so code units above 0x7f
+    // get rejected by the digit check with no extra work
     for i in 0..N {
-        let shift_mask = LENGTH_SHIFT_CONTROL
-            .vecs
-            .get_unchecked(inputs[i].real_length);
+        let [low, high]: [uint16x8_t; 2] = std::mem::transmute(*inputs[i].data);
+        let narrowed = vcombine_u8(vqmovn_u16(low), vqmovn_u16(high));
+        cleaned[i] = vsubq_u8(narrowed, ascii);
+    }
+
+    shift_to_length(&mut cleaned, &inputs.map(|input| input.real_length));
+
+    cleaned
+}
+
+#[inline(always)]
+unsafe fn shift_to_length<const N: usize>(
+    cleaned: &mut [uint8x16_t; N],
+    lengths: &[DecimalLen; N],
+) {
+    for i in 0..N {
+        let shift_mask = LENGTH_SHIFT_CONTROL.vecs.get_unchecked(lengths[i].get());
 
         cleaned[i] = vqtbl1q_u8(cleaned[i], *shift_mask);
     }
+}
+
+/// One bit per byte of a comparison result, like _mm_movemask_epi8
+#[inline(always)]
+unsafe fn movemask(is_eq: uint8x16_t) -> u32 {
+    let weighted = vandq_u8(is_eq, MOVEMASK_BITS.vecs[0]);
+    let low = vaddv_u8(vget_low_u8(weighted)) as u32;
+    let high = vaddv_u8(vget_high_u8(weighted)) as u32;
+    low | (high << 8)
+}
+
+#[inline(always)]
+unsafe fn remove_dots<const N: usize, O: OutputColumns<N>>(
+    cleaned: &mut [uint8x16_t; N],
+    outputs: &mut O,
+    decimal_separator: u8,
+) {
+    let dot = vdupq_n_u8(decimal_separator.wrapping_sub(b'0'));
 
     // https://community.arm.com/arm-community-blogs/b/infrastructure-solutions-blog/posts/porting-x86-vector-bitmask-optimizations-to-arm-neon
-    if !KNOWN_INTEGER {
-        let mut exploded_dot_mask: [u64; N] = [0; N];
-        let mut dot_idx: [u32; N] = [0; N];
-        for i in 0..N {
-            let is_eq_dot = vceqq_u8(cleaned[i], dot);
+    let mut exploded_dot_mask: [u64; N] = [0; N];
+    let mut dot_idx: [u32; N] = [0; N];
+    for i in 0..N {
+        let is_eq_dot = vceqq_u8(cleaned[i], dot);
 
-            let is_eq_fot_16 = vreinterpretq_u16_u8(is_eq_dot);
+        let is_eq_fot_16 = vreinterpretq_u16_u8(is_eq_dot);
 
-            let is_eq_dot_4x_vec_mask = vshrn_n_u16(is_eq_fot_16, 4);
+        let is_eq_dot_4x_vec_mask = vshrn_n_u16(is_eq_fot_16, 4);
 
-            exploded_dot_mask[i] = vget_lane_u64(vreinterpret_u64_u8(is_eq_dot_4x_vec_mask), 0);
-        }
+        exploded_dot_mask[i] = vget_lane_u64(vreinterpret_u64_u8(is_eq_dot_4x_vec_mask), 0);
+    }
 
-        for i in 0..N {
-            dot_idx[i] = exploded_dot_mask[i].trailing_zeros() / 4;
-        }
+    for i in 0..N {
+        dot_idx[i] = exploded_dot_mask[i].trailing_zeros() / 4;
+    }
 
-        for i in 0..N {
-            // arm has a fast saturating sub instruction
+    let mut exponents = [0; N];
+    for i in 0..N {
+        // arm has a fast saturating sub instruction
 
-            outputs[i].exponent = *EXPONENT_FROM_BITS.get_unchecked(dot_idx[i] as usize);
+        exponents[i] = *EXPONENT_FROM_BITS.get_unchecked(dot_idx[i] as usize);
 
-            let dot_control = DOT_SHUFFLE_CONTROL.vecs.get_unchecked(dot_idx[i] as usize);
+        let dot_control = DOT_SHUFFLE_CONTROL.vecs.get_unchecked(dot_idx[i] as usize);
 
-            cleaned[i] = vqtbl1q_u8(cleaned[i], *dot_control);
-        }
+        cleaned[i] = vqtbl1q_u8(cleaned[i], *dot_control);
+    }
+    outputs.set_exponents(exponents);
+}
+
+/// Validates the thousands separators in each lane and compacts them out.
+/// Returns false if any lane has badly placed separators
+#[inline(always)]
+unsafe fn remove_groups<const N: usize, const KNOWN_INTEGER: bool>(
+    cleaned: &mut [uint8x16_t; N],
+    inputs: &[ParseInput; N],
+    decimal_separator: u8,
+    group_separator: u8,
+) -> bool {
+    let group = vdupq_n_u8(group_separator.wrapping_sub(b'0'));
+    let dot = vdupq_n_u8(decimal_separator.wrapping_sub(b'0'));
+    let mut all_valid = true;
+
+    for i in 0..N {
+        let group_mask = movemask(vceqq_u8(cleaned[i], group));
+
+        let dot_idx = if KNOWN_INTEGER {
+            16
+        } else {
+            (movemask(vceqq_u8(cleaned[i], dot)) | 0xffff_0000).trailing_zeros()
+        };
+
+        all_valid &= grouping_is_valid(group_mask, dot_idx, inputs[i].real_length);
+
+        let group_control = GROUP_SHUFFLE_CONTROL
+            .vecs
+            .get_unchecked(group_shuffle_index(group_mask));
+
+        cleaned[i] = vqtbl1q_u8(cleaned[i], *group_control);
     }
 
+    all_valid
+}
+
+/// Zeroes out spaces at the front of each lane.
+/// Returns false if any lane has spaces after a digit
+#[inline(always)]
+unsafe fn align_to_scale<const N: usize>(
+    cleaned: &mut [uint8x16_t; N],
+    parsed: &[ParseOutput; N],
+    vector_scale: u8,
+) -> [bool; N] {
+    let zero = vdupq_n_u8(0);
+    let mut aligned = [false; N];
+
+    for i in 0..N {
+        let zero_mask = movemask(vceqq_u8(cleaned[i], zero));
+        let shift = scale_shift(zero_mask, parsed[i].exponent, vector_scale);
+        aligned[i] = shift.is_some();
+
+        let scale_control = SCALE_SHIFT_CONTROL
+            .vecs
+            .get_unchecked(shift.unwrap_or(0) as usize);
+
+        cleaned[i] = vqtbl1q_u8(cleaned[i], *scale_control);
+    }
+
+    aligned
+}
+
+#[inline(always)]
+unsafe fn remove_left_padding<const N: usize>(
+    cleaned: &mut [uint8x16_t; N],
+    inputs: &[ParseInput; N],
+) -> bool {
+    let space = vdupq_n_u8((b' ').wrapping_sub(b'0'));
+    let mut all_valid = true;
+
+    for i in 0..N {
+        let is_space = vceqq_u8(cleaned[i], space);
+
+        all_valid &= left_padding_is_valid(movemask(is_space), inputs[i].real_length);
+
+        cleaned[i] = vbicq_u8(cleaned[i], is_space);
+    }
+
+    all_valid
+}
+
+/// Returns false if any lane is outside of the strict grammar
+#[inline(always)]
+unsafe fn check_strict_grammar<const N: usize, const KNOWN_INTEGER: bool>(
+    cleaned: &[uint8x16_t; N],
+    inputs: &[ParseInput; N],
+) -> bool {
+    let zero = vdupq_n_u8(0);
+    let dot = vdupq_n_u8((b'.').wrapping_sub(b'0'));
+    let mut all_valid = true;
+
+    for i in 0..N {
+        let zero_mask = movemask(vceqq_u8(cleaned[i], zero));
+
+        let dot_idx = if KNOWN_INTEGER {
+            16
+        } else {
+            (movemask(vceqq_u8(cleaned[i], dot)) | 0xffff_0000).trailing_zeros()
+        };
+
+        all_valid &= strict_grammar_is_valid(zero_mask, dot_idx, inputs[i].real_length);
+    }
+
+    all_valid
+}
+
+/// Returns true if every byte of every vector is a digit
+#[inline(always)]
+unsafe fn check_digits<const N: usize>(cleaned: &[uint8x16_t; N]) -> bool {
     let mut all_masks = vdupq_n_u8(0);
 
     let ten = vdupq_n_u8(10);
-    for cl in &cleaned {
+    for cl in cleaned {
         let greater_equal_ten = vcgeq_u8(*cl, ten);
 
         all_masks = vorrq_u8(all_masks, greater_equal_ten);
     }
 
     // arm version to test all zeros
-    let any_bad_ones = vaddvq_u64(vreinterpretq_u64_u8(all_masks));
+    vaddvq_u64(vreinterpretq_u64_u8(all_masks)) == 0
+}
 
-    if any_bad_ones != 0 {
-        return false;
+/// Returns a mask with bit i set if every byte of vector i is a digit
+#[inline(always)]
+unsafe fn digit_lanes<const N: usize>(cleaned: &[uint8x16_t; N]) -> u32 {
+    let ten = vdupq_n_u8(10);
+    let mut valid = 0;
+    for (i, cl) in cleaned.iter().enumerate() {
+        let is_digit = vcltq_u8(*cl, ten);
+
+        // every byte is all ones only if the whole vector is digits
+        valid |= ((vminvq_u8(is_digit) == u8::MAX) as u32) << i;
+    }
+    valid
+}
+
+#[inline(always)]
+unsafe fn reduce_to_mantissas<const N: usize, O: OutputColumns<N>>(
+    cleaned: &mut [uint8x16_t; N],
+    outputs: &mut O,
+) {
+    reduce_to_halves(cleaned);
+
+    // NEON has no 64-bit integer multiply, sadly.
+    // However, we know that the above each fit into a u32 still
+    // TODO I'm assuming these multiplications happen in 64-bit space,
+    // and that's why there's no vector equivalent for larger.
+    // need to test...
+
+    // TO BENCHMARK: should compare the simple swizzle, extract,
+    // and perform in integer space?
+    let mut mantissas = [0; N];
+    for i in 0..N {
+        let as_32 = vreinterpretq_u32_u8(cleaned[i]);
+        let small = vmovl_u32(vget_low_u32(as_32));
+        let acc = vmlal_high_n_u32(small, as_32, 1_00_00_00_00);
+        mantissas[i] = vgetq_lane_u64(acc, 0);
     }
+    outputs.set_mantissas(mantissas);
+}
+
+/// Reduces each group of 4 digits into the u32 lane holding them.
+/// This doesn't share the shuffled reduction above, which mixes digits across the lanes
+#[inline(always)]
+unsafe fn reduce_to_quads<const N: usize>(cleaned: &[uint8x16_t; N]) -> [uint32x4_t; N] {
+    // [10, 1] in every u16, and [100, 1] in every u32
+    let mul_1_10 = vreinterpretq_u8_u16(vdupq_n_u16(0x010a));
+    let mul_1_100 = vreinterpretq_u16_u32(vdupq_n_u32(0x0001_0064));
+
+    let mut quads = [vdupq_n_u32(0); N];
+    for i in 0..N {
+        let low = vmull_u8(vget_low_u8(cleaned[i]), vget_low_u8(mul_1_10));
+        let high = vmull_high_u8(cleaned[i], mul_1_10);
+        let pairs = vpaddq_u16(low, high);
+        quads[i] = vpaddlq_u16(vmulq_u16(pairs, mul_1_100));
+    }
+    quads
+}
+
+/// Reduces each lane up to the last multiply, leaving the bottom eight digits
+/// in the low u64 and the top eight digits in the high u64
+#[inline(always)]
+unsafe fn reduce_to_halves<const N: usize>(cleaned: &mut [uint8x16_t; N]) {
+    let ten = vdupq_n_u8(10);
 
     // Now, all that we do is convert to an actual integer
 
@@ -180,7 +968,7 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     // but no other details change
 
     let acc_shuffle = SHUFFLE_ACC.vecs[0];
-    for cl in &mut cleaned {
+    for cl in cleaned.iter_mut() {
         *cl = vqtbl1q_u8(*cl, acc_shuffle);
     }
 
@@ -194,40 +982,23 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
 
     // I'm not a neon expert, would be glad to find a faster way to do th
 
-    for cl in &mut cleaned {
+    for cl in cleaned.iter_mut() {
         let small = vmovl_u8(vget_low_u8(*cl));
         let acc = vmlal_high_u8(small, *cl, ten);
         *cl = vreinterpretq_u8_u16(acc);
     }
 
-    for cl in &mut cleaned {
+    for cl in cleaned.iter_mut() {
         let as_16 = vreinterpretq_u16_u8(*cl);
         let small = vmovl_u16(vget_low_u16(as_16));
         let acc = vmlal_high_n_u16(small, as_16, 1_00);
         *cl = vreinterpretq_u8_u32(acc);
     }
 
-    for cl in &mut cleaned {
+    for cl in cleaned.iter_mut() {
         let as_32 = vreinterpretq_u32_u8(*cl);
         let small = vmovl_u32(vget_low_u32(as_32));
         let acc = vmlal_high_n_u32(small, as_32, 1_00_00);
         *cl = vreinterpretq_u8_u64(acc);
     }
-
-    // NEON has no 64-bit integer multiply, sadly.
-    // However, we know that the above each fit into a u32 still
-    // TODO I'm assuming these multiplications happen in 64-bit space,
-    // and that's why there's no vector equivalent for larger.
-    // need to test...
-
-    // TO BENCHMARK: should compare the simple swizzle, extract,
-    // and perform in integer space?
-    for i in 0..N {
-        let as_32 = vreinterpretq_u32_u8(cleaned[i]);
-        let small = vmovl_u32(vget_low_u32(as_32));
-        let acc = vmlal_high_n_u32(small, as_32, 1_00_00_00_00);
-        outputs[i].mantissa = vgetq_lane_u64(acc, 0);
-    }
-
-    any_bad_ones == 0
 }
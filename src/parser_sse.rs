@@ -1,26 +1,53 @@
 use std::arch::x86_64::{
-    _mm_andnot_si128, _mm_cmpeq_epi8, _mm_cvtsi128_si64, _mm_madd_epi16, _mm_maddubs_epi16,
-    _mm_max_epu8, _mm_movemask_epi8, _mm_packs_epi32, _mm_set1_epi8, _mm_setr_epi16, _mm_setr_epi8,
-    _mm_shuffle_epi8, _mm_sub_epi8, _mm_test_all_ones,
+    __m128i, _mm_and_si128, _mm_andnot_si128, _mm_cmpeq_epi8, _mm_cmpgt_epi8, _mm_cvtsi128_si64,
+    _mm_extract_epi8, _mm_madd_epi16, _mm_maddubs_epi16, _mm_max_epu8, _mm_movemask_epi8,
+    _mm_or_si128, _mm_packs_epi32, _mm_packus_epi16, _mm_set1_epi16, _mm_set1_epi8, _mm_setr_epi16,
+    _mm_setr_epi8, _mm_shuffle_epi8, _mm_slli_si128, _mm_srli_epi16, _mm_sub_epi8,
+    _mm_test_all_ones,
 };
 
-use crate::tables::{DOT_SHUFFLE_CONTROL, EXPONENT_FROM_BITS, LENGTH_SHIFT_CONTROL};
-use crate::{ParseInput, ParseOutput};
+use crate::cobol::{apply_signs, packed_sign_is_negative, remove_overpunch, MAX_PACKED_BYTES};
+use crate::columns::{OutputColumns, SplitOutputs};
+use crate::filter::{digits_at_least, Alignment, Threshold};
+use crate::grouping::{group_shuffle_index, grouping_is_valid};
+use crate::integers::{
+    remove_minus_sign, u32_from_halves, HALF_DIGITS, MAX_SMALL_LENGTH, MAX_U32_DIGITS,
+    SMALL_LANE_OFFSET, SMALL_PER_VECTOR,
+};
+use crate::ordering::sort_key;
+use crate::padding::left_padding_is_valid;
+use crate::strict::strict_grammar_is_valid;
+use crate::sum::{mantissa_at_scale, scale_shift, sum_at_scale, MAX_VECTOR_SCALE};
+use crate::tables::{
+    DOT_SHUFFLE_CONTROL, EXPONENT_FROM_BITS, GROUP_SHUFFLE_CONTROL, LENGTH_SHIFT_CONTROL,
+    PACKED_SHUFFLE_CONTROL, SCALE_SHIFT_CONTROL, SMALL_SHIFT_CONTROL,
+};
+use crate::{DecimalLen, ParseInput, ParseInput16, ParseOutput, SignedParseOutput};
 
 /// Parses the inputs passed into (mantissa, exponent) pairs.
 /// If any of them detected invalid, returns false
 /// # Safety
 ///
-/// It is unsafe to pass anything with a real_length that is greater than 16
+/// It is unsafe to call this on a cpu without ssse3 and sse4.1.
+/// Lengths are bounded by [`crate::DecimalLen`] so the table lookups are always in range
 #[inline]
 pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     inputs: &[ParseInput; N],
     outputs: &mut [ParseOutput; N],
 ) -> bool {
-    let ascii = _mm_set1_epi8(b'0' as i8);
-    let dot = _mm_set1_epi8((b'.').wrapping_sub(b'0') as i8);
-    let mut cleaned = [_mm_set1_epi8(0); N];
+    do_parse_decimals_with_separator::<N, KNOWN_INTEGER>(inputs, outputs, b'.')
+}
 
+/// Parses the inputs like [`do_parse_decimals`], with decimal_separator in place of the dot
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_decimals_with_separator<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    decimal_separator: u8,
+) -> bool {
     // PERF
     // I did some expermients to hoist the dot-discovery code above the length shifting code,
     // to try and remove a data dependency. This surprisingly really hurt performance,
@@ -29,17 +56,578 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
 
     // This is done as a series of many loops to maximise the instant parallelism available to the
     // cpu. It's semantically identical but means the decoder doesn't have to churn through
-    // many copies of the code to find independent instructions
+    // many copies of the code to find independent instructions.
+    // Each stage is its own always-inlined function so that the other kernels can share them,
+    // but they still get compiled into one flat series of loops
+    let mut cleaned = load_and_shift(inputs);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, decimal_separator);
+    }
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    all_good
+}
+
+/// Parses utf-16 inputs like [`do_parse_decimals`]
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_decimals_utf16<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput16; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    let mut cleaned = load_and_shift_utf16(inputs);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, b'.');
+    }
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    all_good
+}
+
+/// Parses the inputs like [`do_parse_decimals`], writing the mantissas and exponents
+/// into separate arrays
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_decimals_columnar<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    mantissas: &mut [u64; N],
+    exponents: &mut [u8; N],
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+    let mut outputs = SplitOutputs {
+        mantissas,
+        exponents,
+    };
+
+    if KNOWN_INTEGER {
+        outputs.set_exponents([0; N]);
+    } else {
+        remove_dots(&mut cleaned, &mut outputs, b'.');
+    }
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, &mut outputs);
+
+    all_good
+}
+
+/// Parses the inputs as plain unsigned integers
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_u64s<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u64; N],
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    all_good
+}
+
+/// Parses the inputs as plain unsigned integers, and returns false if any have more than
+/// ten digits or don't fit in a u32
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_u32s<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u32; N],
+) -> bool {
+    let longest = inputs
+        .iter()
+        .map(|input| input.real_length.get())
+        .max()
+        .unwrap_or(0);
+
+    let mut cleaned = load_and_shift(inputs);
+
+    let all_good = check_digits(&cleaned);
+
+    let u32_pairs = reduce_to_halves(&mut cleaned);
+
+    // When nothing reaches the top eight digits the last multiply can be skipped entirely
+    let mut fits = longest <= MAX_U32_DIGITS;
+    if longest <= HALF_DIGITS {
+        for i in 0..N {
+            outputs[i] = (u32_pairs[i] >> 32) as u32;
+        }
+    } else {
+        for i in 0..N {
+            let combined = u32_from_halves(u32_pairs[i] as u32, (u32_pairs[i] >> 32) as u32);
+            fits &= combined.is_some();
+            outputs[i] = combined.unwrap_or(0);
+        }
+    }
+
+    all_good & fits
+}
+
+/// Parses the inputs as plain integers with an optional leading minus sign
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_i64s<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [i64; N],
+) -> bool {
+    // Like the zoned decimals, the sign is removed on a copy before anything is loaded
+    let mut data = [[0; 16]; N];
+    let mut negative = [false; N];
+    for i in 0..N {
+        data[i] = *inputs[i].data;
+        negative[i] = remove_minus_sign(&mut data[i], inputs[i].real_length.get());
+    }
+
+    let unsigned_inputs: [ParseInput; N] = std::array::from_fn(|i| ParseInput {
+        data: &data[i],
+        real_length: inputs[i].real_length,
+    });
+    let mut cleaned = load_and_shift(&unsigned_inputs);
+
+    let all_good = check_digits(&cleaned);
+
+    let mut unsigned = [0u64; N];
+    reduce_to_mantissas(&mut cleaned, &mut unsigned);
+
+    // At most 16 digits always fit, so negating can't overflow
+    for i in 0..N {
+        let magnitude = unsigned[i] as i64;
+        outputs[i] = if negative[i] { -magnitude } else { magnitude };
+    }
+
+    all_good
+}
+
+/// Parses up to four integers of at most 4 bytes, all gathered into a single vector.
+/// Returns false if any are invalid or too long
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]. Panics if N is more than [`SMALL_PER_VECTOR`]
+#[inline]
+pub unsafe fn do_parse_small_integers<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [u32; N],
+) -> bool {
+    // Each input gets a u32 of the vector, and the lanes without one are zero filled
+    let mut words = [0; SMALL_PER_VECTOR];
+    let mut controls = [SMALL_SHIFT_CONTROL[0]; SMALL_PER_VECTOR];
+    let mut lengths_good = true;
+    for i in 0..N {
+        let length = inputs[i].real_length.get();
+        lengths_good &= length <= MAX_SMALL_LENGTH;
+
+        let mut bytes = [0; MAX_SMALL_LENGTH];
+        bytes.copy_from_slice(&inputs[i].data[..MAX_SMALL_LENGTH]);
+        words[i] = u32::from_le_bytes(bytes);
+        controls[i] =
+            SMALL_SHIFT_CONTROL[length.min(MAX_SMALL_LENGTH)] + SMALL_LANE_OFFSET * i as u32;
+    }
+
+    // One shuffle right-aligns all of them at once
+    let loaded: __m128i = std::mem::transmute(words);
+    let control: __m128i = std::mem::transmute(controls);
+    let ascii = _mm_set1_epi8(b'0' as i8);
+    let mut cleaned = [_mm_shuffle_epi8(_mm_sub_epi8(loaded, ascii), control)];
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_quads(&mut cleaned);
+
+    let values: [u32; SMALL_PER_VECTOR] = std::mem::transmute(cleaned[0]);
+    outputs.copy_from_slice(&values[..N]);
+
+    all_good & lengths_good
+}
+
+/// Parses the inputs like [`do_parse_decimals`] and adds them up at the given scale.
+/// Returns None if any are invalid, have more decimals than scale, or the sum overflows
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_sum_decimals<const N: usize>(inputs: &[ParseInput; N], scale: u8) -> Option<i128> {
+    let mut cleaned = load_and_shift(inputs);
+
+    let mut parsed = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut parsed, b'.');
+
+    // Lining the digits up before the reduction means the mantissas can just be added,
+    // instead of multiplying each one by a power of ten
+    let vector_scale = scale.min(MAX_VECTOR_SCALE);
+    let aligned = align_to_scale(&mut cleaned, &parsed, vector_scale);
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, &mut parsed);
+
+    if !all_good {
+        return None;
+    }
+
+    sum_at_scale(&parsed, &aligned, vector_scale, scale)
+}
+
+/// Checks the inputs like [`do_validate_decimals`], and compares them against threshold.
+/// Returns a mask of the valid inputs which are at least threshold, and the mask of valid inputs
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_filter_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    threshold: ParseOutput,
+) -> (u32, u32) {
+    let threshold = Threshold::new(threshold);
+    let threshold_digits: __m128i = std::mem::transmute(threshold.digits);
+
+    let mut cleaned = load_and_shift(inputs);
+
+    let mut parsed = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut parsed, b'.');
+
+    let valid = digit_lanes(&cleaned);
+
+    // Digits only go up to 9, so the signed comparisons are fine
+    let zero = _mm_set1_epi8(0);
+    let mut at_least = 0;
+    for i in 0..N {
+        let zero_mask = _mm_movemask_epi8(_mm_cmpeq_epi8(cleaned[i], zero)) as u32;
+
+        let lane_at_least = match threshold.align(zero_mask, parsed[i].exponent) {
+            Alignment::Known(answer) => answer,
+            Alignment::Shift {
+                lane,
+                threshold: threshold_shift,
+            } => {
+                let lane_control = SCALE_SHIFT_CONTROL.vecs.get_unchecked(lane as usize);
+                let threshold_control = SCALE_SHIFT_CONTROL
+                    .vecs
+                    .get_unchecked(threshold_shift as usize);
+                let lane = _mm_shuffle_epi8(cleaned[i], *lane_control);
+                let threshold = _mm_shuffle_epi8(threshold_digits, *threshold_control);
+
+                let greater = _mm_movemask_epi8(_mm_cmpgt_epi8(lane, threshold)) as u32;
+                let less = _mm_movemask_epi8(_mm_cmpgt_epi8(threshold, lane)) as u32;
+                digits_at_least(greater, less)
+            }
+        };
+
+        at_least |= (lane_at_least as u32) << i;
+    }
+
+    (at_least & valid, valid)
+}
+
+/// Parses the inputs like [`do_parse_decimals`] into keys which sort the same way
+/// as the values do at the given scale. Returns false if any are invalid,
+/// have more decimals than scale, or don't fit in an i64 at scale
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_sort_key_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    keys: &mut [u64; N],
+    scale: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    let mut parsed = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut parsed, b'.');
+
+    // The same alignment as summing, so most lanes come out of the reduction already at scale
+    let vector_scale = scale.min(MAX_VECTOR_SCALE);
+    let aligned = align_to_scale(&mut cleaned, &parsed, vector_scale);
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, &mut parsed);
+
+    let mut keys_good = true;
+    for i in 0..N {
+        let mantissa = mantissa_at_scale(parsed[i], aligned[i], vector_scale, scale)
+            .and_then(|mantissa| i64::try_from(mantissa).ok());
+        keys_good &= mantissa.is_some();
+        keys[i] = sort_key(mantissa.unwrap_or(0));
+    }
+
+    all_good & keys_good
+}
+
+/// Checks the inputs like [`do_parse_decimals`] without computing mantissas.
+/// Returns a mask with bit i set if input i is valid
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_validate_decimals<const N: usize>(inputs: &[ParseInput; N]) -> u32 {
+    let mut cleaned = load_and_shift(inputs);
+
+    // The exponents are never read, so these stores get optimized out
+    let mut exponents = [ParseOutput::default(); N];
+    remove_dots(&mut cleaned, &mut exponents, b'.');
+
+    digit_lanes(&cleaned)
+}
+
+/// Parses the inputs as fixed-width integers which may be padded on the left with spaces,
+/// and gives them all the same exponent
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_implied<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    exponent: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    // After this, the field is exactly what the integer path expects
+    let padding_good = remove_left_padding(&mut cleaned, inputs);
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    for output in outputs.iter_mut() {
+        output.exponent = exponent;
+    }
+
+    all_good & padding_good
+}
+
+/// Parses the inputs like [`do_parse_decimals`], but rejects anything outside of
+/// the JSON number grammar
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_decimals_strict<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    // This has to look at the dot and leading zero before they get shuffled away
+    let grammar_good = check_strict_grammar::<N, KNOWN_INTEGER>(&cleaned, inputs);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, b'.');
+    }
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    all_good & grammar_good
+}
+
+/// Parses the inputs like [`do_parse_decimals_with_separator`], but first removes thousands separators
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_decimals_grouped<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+    decimal_separator: u8,
+    group_separator: u8,
+) -> bool {
+    let mut cleaned = load_and_shift(inputs);
+
+    // Separators all come before the dot, so removing them doesn't move the dot
+    let groups_good =
+        remove_groups::<N, KNOWN_INTEGER>(&mut cleaned, inputs, decimal_separator, group_separator);
+
+    if !KNOWN_INTEGER {
+        remove_dots(&mut cleaned, outputs, decimal_separator);
+    }
+
+    let all_good = check_digits(&cleaned);
+
+    reduce_to_mantissas(&mut cleaned, outputs);
+
+    all_good & groups_good
+}
+
+/// Parses the inputs as packed decimals, where real_length is the number of bytes
+/// and the sign is in the last nibble. Every output gets the same exponent
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_packed_decimals<const N: usize>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    exponent: u8,
+) -> bool {
+    let mut cleaned = [_mm_set1_epi8(0); N];
+    let mut signs = [0; N];
+    let mut lengths_good = true;
+
+    // Every byte lands in two neighbouring lanes, the left one keeps the high nibble
+    // and the right one the low nibble. That leaves the sign nibble in the last lane
+    for i in 0..N {
+        let length = inputs[i].real_length.get();
+        lengths_good &= (1..=MAX_PACKED_BYTES).contains(&length);
+
+        let loaded = std::mem::transmute::<[u8; 16], __m128i>(*inputs[i].data);
+        let packed_control = PACKED_SHUFFLE_CONTROL
+            .vecs
+            .get_unchecked(length.min(MAX_PACKED_BYTES));
+
+        cleaned[i] = _mm_shuffle_epi8(loaded, *packed_control);
+    }
+
+    // There's no 8 bit shift, but the bits a 16 bit shift drags across lanes get masked off
+    let high_lanes = _mm_set1_epi16(0x000f);
+    let low_lanes = _mm_set1_epi16(0x0f00);
+    for cl in cleaned.iter_mut() {
+        let high = _mm_and_si128(_mm_srli_epi16(*cl, 4), high_lanes);
+        let low = _mm_and_si128(*cl, low_lanes);
+        *cl = _mm_or_si128(high, low);
+    }
+
+    // Shifting out the sign leaves 15 right-aligned digits, the same as a cleaned decimal
+    for i in 0..N {
+        signs[i] = _mm_extract_epi8(cleaned[i], 15) as u8;
+        cleaned[i] = _mm_slli_si128(cleaned[i], 1);
+    }
+
+    let mut negative = [false; N];
+    let mut signs_good = true;
+    for i in 0..N {
+        let sign = packed_sign_is_negative(signs[i]);
+        signs_good &= sign.is_some();
+        negative[i] = sign == Some(true);
+    }
+
+    let all_good = check_digits(&cleaned);
+
+    let mut unsigned = [ParseOutput::default(); N];
+    reduce_to_mantissas(&mut cleaned, &mut unsigned);
+
+    apply_signs(&unsigned, &negative, exponent, outputs);
+
+    all_good & lengths_good & signs_good
+}
+
+/// Parses the inputs as zoned decimals, where the sign is overpunched onto the last digit.
+/// They are EBCDIC if that's set and ASCII otherwise. Every output gets the same exponent
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn do_parse_zoned_decimals<const N: usize, const EBCDIC: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [SignedParseOutput; N],
+    exponent: u8,
+) -> bool {
+    // The overpunch is undone on a copy before anything is loaded,
+    // which leaves a plain integer for the usual subtract and range check
+    let mut data = [[0; 16]; N];
+    let mut negative = [false; N];
+    let mut lengths_good = true;
+    for i in 0..N {
+        data[i] = *inputs[i].data;
+        let sign = remove_overpunch::<EBCDIC>(&mut data[i], inputs[i].real_length.get());
+        lengths_good &= sign.is_some();
+        negative[i] = sign == Some(true);
+    }
+
+    let unpunched: [ParseInput; N] = std::array::from_fn(|i| ParseInput {
+        data: &data[i],
+        real_length: inputs[i].real_length,
+    });
+    let mut cleaned = load_and_shift(&unpunched);
+
+    let all_good = check_digits(&cleaned);
+
+    let mut unsigned = [ParseOutput::default(); N];
+    reduce_to_mantissas(&mut cleaned, &mut unsigned);
+
+    apply_signs(&unsigned, &negative, exponent, outputs);
+
+    all_good & lengths_good
+}
+
+/// Compares a chunk against each of the bytes, and returns a mask per byte
+/// with bit i set if chunk[i] is equal to it. This is used to find structure, not parse
+/// # Safety
+///
+/// Same as [`do_parse_decimals`]
+#[inline]
+pub unsafe fn find_byte_masks<const M: usize>(chunk: &[u8; 16], bytes: [u8; M]) -> [u32; M] {
+    let loaded: __m128i = std::mem::transmute(*chunk);
+    let mut masks = [0; M];
+    for i in 0..M {
+        let is_eq = _mm_cmpeq_epi8(loaded, _mm_set1_epi8(bytes[i] as i8));
+        masks[i] = _mm_movemask_epi8(is_eq) as u32;
+    }
+    masks
+}
+
+#[inline(always)]
+unsafe fn load_and_shift<const N: usize>(inputs: &[ParseInput; N]) -> [__m128i; N] {
+    let ascii = _mm_set1_epi8(b'0' as i8);
+    let mut cleaned = [_mm_set1_epi8(0); N];
 
     // first, load data and subtract off the ascii mask
     // Everything in the range '0'..'9' will become 0..9
     // everthing else will overflow into 10..256
     for i in 0..N {
         // transumte will just compile to the intrinsics anyways
-        let loaded = std::mem::transmute(*inputs[i].data);
+        let loaded = std::mem::transmute::<[u8; 16], __m128i>(*inputs[i].data);
         cleaned[i] = _mm_sub_epi8(loaded, ascii);
     }
 
+    shift_to_length(&mut cleaned, &inputs.map(|input| input.real_length));
+
+    cleaned
+}
+
+/// Loads utf-16 inputs and narrows them to bytes, then cleans them like load_and_shift
+#[inline(always)]
+unsafe fn load_and_shift_utf16<const N: usize>(inputs: &[ParseInput16; N]) -> [__m128i; N] {
+    let ascii = _mm_set1_epi8(b'0' as i8);
+    let mut cleaned = [_mm_set1_epi8(0); N];
+
+    // packus saturates anything that doesn't fit in a byte to either 0 or 0xff.
+    // Neither of those or anything from 0x80 to 0xff is a digit or a dot, so code units
+    // above 0x7f get rejected by the digit check with no extra work
+    for i in 0..N {
+        let [low, high]: [__m128i; 2] = std::mem::transmute(*inputs[i].data);
+        cleaned[i] = _mm_sub_epi8(_mm_packus_epi16(low, high), ascii);
+    }
+
+    shift_to_length(&mut cleaned, &inputs.map(|input| input.real_length));
+
+    cleaned
+}
+
+#[inline(always)]
+unsafe fn shift_to_length<const N: usize>(cleaned: &mut [__m128i; N], lengths: &[DecimalLen; N]) {
     // now, we convert the string from [1234.123 <garbage>] into [00000 ... 1234.123]
     // as well as insert zeros for everything past the end
 
@@ -47,30 +635,162 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     // contention on port 5 (the shuffle port). You can't do this for a full vector
     // since there's no way to do so without an immediate value
     for i in 0..N {
-        let shift_mask = LENGTH_SHIFT_CONTROL
-            .vecs
-            .get_unchecked(inputs[i].real_length);
+        let shift_mask = LENGTH_SHIFT_CONTROL.vecs.get_unchecked(lengths[i].get());
 
         cleaned[i] = _mm_shuffle_epi8(cleaned[i], *shift_mask);
     }
+}
 
-    if !KNOWN_INTEGER {
-        for i in 0..N {
-            let is_eq_dot = _mm_cmpeq_epi8(cleaned[i], dot);
-            // Set the top 16 bits to 1 as an implicit dot
-            let is_dot_mask = _mm_movemask_epi8(is_eq_dot) as u32 | 0xffff_0000;
+/// Broadcasts a byte the same way load_and_shift cleans the input
+#[inline(always)]
+unsafe fn cleaned_splat(byte: u8) -> __m128i {
+    _mm_set1_epi8(byte.wrapping_sub(b'0') as i8)
+}
 
-            let dot_idx = is_dot_mask.trailing_zeros();
+/// Index of the first dot in a cleaned vector, or 16 if there is none
+#[inline(always)]
+unsafe fn find_dot(cleaned: __m128i, dot: __m128i) -> u32 {
+    let is_eq_dot = _mm_cmpeq_epi8(cleaned, dot);
+    // Set the top 16 bits to 1 as an implicit dot
+    let is_dot_mask = _mm_movemask_epi8(is_eq_dot) as u32 | 0xffff_0000;
 
-            outputs[i].exponent = EXPONENT_FROM_BITS[dot_idx as usize];
-            let dot_control = DOT_SHUFFLE_CONTROL.vecs.get_unchecked(dot_idx as usize);
+    is_dot_mask.trailing_zeros()
+}
 
-            cleaned[i] = _mm_shuffle_epi8(cleaned[i], *dot_control);
-        }
+#[inline(always)]
+unsafe fn remove_dots<const N: usize, O: OutputColumns<N>>(
+    cleaned: &mut [__m128i; N],
+    outputs: &mut O,
+    decimal_separator: u8,
+) {
+    let dot = cleaned_splat(decimal_separator);
+    let mut exponents = [0; N];
+    for i in 0..N {
+        let dot_idx = find_dot(cleaned[i], dot);
+
+        exponents[i] = EXPONENT_FROM_BITS[dot_idx as usize];
+        let dot_control = DOT_SHUFFLE_CONTROL.vecs.get_unchecked(dot_idx as usize);
+
+        cleaned[i] = _mm_shuffle_epi8(cleaned[i], *dot_control);
+    }
+    outputs.set_exponents(exponents);
+}
+
+/// Validates the thousands separators in each lane and compacts them out.
+/// Returns false if any lane has badly placed separators
+#[inline(always)]
+unsafe fn remove_groups<const N: usize, const KNOWN_INTEGER: bool>(
+    cleaned: &mut [__m128i; N],
+    inputs: &[ParseInput; N],
+    decimal_separator: u8,
+    group_separator: u8,
+) -> bool {
+    let group = cleaned_splat(group_separator);
+    let dot = cleaned_splat(decimal_separator);
+    let mut all_valid = true;
+
+    for i in 0..N {
+        let is_eq_group = _mm_cmpeq_epi8(cleaned[i], group);
+        let group_mask = _mm_movemask_epi8(is_eq_group) as u32;
+
+        // Integers have an implicit dot right past the end
+        let dot_idx = if KNOWN_INTEGER {
+            16
+        } else {
+            find_dot(cleaned[i], dot)
+        };
+
+        all_valid &= grouping_is_valid(group_mask, dot_idx, inputs[i].real_length);
+
+        let group_control = GROUP_SHUFFLE_CONTROL
+            .vecs
+            .get_unchecked(group_shuffle_index(group_mask));
+
+        cleaned[i] = _mm_shuffle_epi8(cleaned[i], *group_control);
+    }
+
+    all_valid
+}
+
+/// Shifts each lane towards the top of the vector so that it has vector_scale decimals.
+/// Lanes with too many digits for that are left alone, and are false in the result
+#[inline(always)]
+unsafe fn align_to_scale<const N: usize>(
+    cleaned: &mut [__m128i; N],
+    parsed: &[ParseOutput; N],
+    vector_scale: u8,
+) -> [bool; N] {
+    let zero = _mm_set1_epi8(0);
+    let mut aligned = [false; N];
+
+    for i in 0..N {
+        let zero_mask = _mm_movemask_epi8(_mm_cmpeq_epi8(cleaned[i], zero)) as u32;
+        let shift = scale_shift(zero_mask, parsed[i].exponent, vector_scale);
+        aligned[i] = shift.is_some();
+
+        let scale_control = SCALE_SHIFT_CONTROL
+            .vecs
+            .get_unchecked(shift.unwrap_or(0) as usize);
+
+        cleaned[i] = _mm_shuffle_epi8(cleaned[i], *scale_control);
+    }
+
+    aligned
+}
+
+/// Zeroes out spaces at the front of each lane. The length shift can only pad
+/// with zeros, so this is what lets fixed-width fields be padded with spaces.
+/// Returns false if any lane has spaces after a digit
+#[inline(always)]
+unsafe fn remove_left_padding<const N: usize>(
+    cleaned: &mut [__m128i; N],
+    inputs: &[ParseInput; N],
+) -> bool {
+    let space = cleaned_splat(b' ');
+    let mut all_valid = true;
+
+    for i in 0..N {
+        let is_space = _mm_cmpeq_epi8(cleaned[i], space);
+        let space_mask = _mm_movemask_epi8(is_space) as u32;
+
+        all_valid &= left_padding_is_valid(space_mask, inputs[i].real_length);
+
+        cleaned[i] = _mm_andnot_si128(is_space, cleaned[i]);
     }
 
+    all_valid
+}
+
+/// Returns false if any lane is outside of the strict grammar
+#[inline(always)]
+unsafe fn check_strict_grammar<const N: usize, const KNOWN_INTEGER: bool>(
+    cleaned: &[__m128i; N],
+    inputs: &[ParseInput; N],
+) -> bool {
+    let zero = _mm_set1_epi8(0);
+    let dot = cleaned_splat(b'.');
+    let mut all_valid = true;
+
+    for i in 0..N {
+        let zero_mask = _mm_movemask_epi8(_mm_cmpeq_epi8(cleaned[i], zero)) as u32;
+
+        let dot_idx = if KNOWN_INTEGER {
+            16
+        } else {
+            find_dot(cleaned[i], dot)
+        };
+
+        all_valid &= strict_grammar_is_valid(zero_mask, dot_idx, inputs[i].real_length);
+    }
+
+    all_valid
+}
+
+/// Returns true if every byte of every vector is a digit
+#[inline(always)]
+unsafe fn check_digits<const N: usize>(cleaned: &[__m128i; N]) -> bool {
     let mut all_masks = _mm_set1_epi8(-1);
-    for cl in &cleaned {
+    for cl in cleaned {
         // take the unsigned max of '9' and anything in the vector
         // then check for equality to '9'
 
@@ -84,23 +804,48 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
         all_masks = _mm_andnot_si128(remaining, all_masks);
     }
 
-    let any_bad_ones = _mm_test_all_ones(all_masks);
+    _mm_test_all_ones(all_masks) == 1
+}
 
-    // Now, all that we do is convert to an actual integer
+/// Returns a mask with bit i set if every byte of vector i is a digit
+#[inline(always)]
+unsafe fn digit_lanes<const N: usize>(cleaned: &[__m128i; N]) -> u32 {
+    let nine = _mm_set1_epi8(9);
+    let mut valid = 0;
+    for (i, cl) in cleaned.iter().enumerate() {
+        let max_of_nine = _mm_max_epu8(nine, *cl);
+        let is_digit = _mm_cmpeq_epi8(max_of_nine, nine);
 
-    // Take pairs of u8s (digits) and multiply the more significant one by 10,
-    // and accumulate into pairwise u16
-    for cl in &mut cleaned {
-        let mul_1_10 = _mm_setr_epi8(10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1);
-        *cl = _mm_maddubs_epi16(*cl, mul_1_10);
+        valid |= ((_mm_movemask_epi8(is_digit) == 0xffff) as u32) << i;
     }
+    valid
+}
 
-    // Take pairs of u16s (not digits, but two digits each)
-    // multiply the more significant by 100 and add to get pairwise u32
-    for cl in &mut cleaned {
-        let mul_1_100 = _mm_setr_epi16(100, 1, 100, 1, 100, 1, 100, 1);
-        *cl = _mm_madd_epi16(*cl, mul_1_100);
+#[inline(always)]
+unsafe fn reduce_to_mantissas<const N: usize, O: OutputColumns<N>>(
+    cleaned: &mut [__m128i; N],
+    outputs: &mut O,
+) {
+    let u32_pairs = reduce_to_halves(cleaned);
+
+    let mut mantissas = [0; N];
+    for i in 0..N {
+        let small_bottom = u32_pairs[i] >> 32;
+
+        // I used to have some code here where you could statically specify
+        // there were less than 8 digits, but it had almost no performance impact
+
+        let large_half = u32_pairs[i] as u32 as u64;
+        mantissas[i] = 100000000 * large_half + small_bottom;
     }
+    outputs.set_mantissas(mantissas);
+}
+
+/// Reduces each lane up to the last multiply, returning the top eight digits
+/// in the low half of each u64 and the bottom eight digits in the high half
+#[inline(always)]
+unsafe fn reduce_to_halves<const N: usize>(cleaned: &mut [__m128i; N]) -> [u64; N] {
+    reduce_to_quads(cleaned);
 
     // We now have pairwise u32s, but there are no methods to multiply and horizontally add
     // them. Doing it outright is *very* slow.
@@ -108,7 +853,7 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     // into the first and second half of the vector
     // Each vector half will now be identical.
 
-    for cl in &mut cleaned {
+    for cl in cleaned.iter_mut() {
         *cl = _mm_packs_epi32(*cl, *cl);
     }
 
@@ -124,7 +869,7 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     // 4. Doesn't require load slots for the constant (low impact imo)
     // will just have to benchmark both
 
-    for cl in &mut cleaned {
+    for cl in cleaned.iter_mut() {
         let mul_1_10000 = _mm_setr_epi16(10000, 1, 10000, 1, 10000, 1, 10000, 1);
         *cl = _mm_madd_epi16(*cl, mul_1_10000);
     }
@@ -133,16 +878,25 @@ pub unsafe fn do_parse_decimals<const N: usize, const KNOWN_INTEGER: bool>(
     for i in 0..N {
         u32_pairs[i] = _mm_cvtsi128_si64(cleaned[i]) as u64;
     }
+    u32_pairs
+}
 
-    for i in 0..N {
-        let small_bottom = u32_pairs[i] >> 32;
-
-        // I used to have some code here where you could statically specify
-        // there were less than 8 digits, but it had almost no performance impact
+/// Reduces each group of 4 digits into the u32 lane holding them
+#[inline(always)]
+unsafe fn reduce_to_quads<const N: usize>(cleaned: &mut [__m128i; N]) {
+    // Now, all that we do is convert to an actual integer
 
-        let large_half = u32_pairs[i] as u32 as u64;
-        outputs[i].mantissa = 100000000 * large_half + small_bottom;
+    // Take pairs of u8s (digits) and multiply the more significant one by 10,
+    // and accumulate into pairwise u16
+    for cl in cleaned.iter_mut() {
+        let mul_1_10 = _mm_setr_epi8(10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1, 10, 1);
+        *cl = _mm_maddubs_epi16(*cl, mul_1_10);
     }
 
-    any_bad_ones == 1
+    // Take pairs of u16s (not digits, but two digits each)
+    // multiply the more significant by 100 and add to get pairwise u32
+    for cl in cleaned.iter_mut() {
+        let mul_1_100 = _mm_setr_epi16(100, 1, 100, 1, 100, 1, 100, 1);
+        *cl = _mm_madd_epi16(*cl, mul_1_100);
+    }
 }
@@ -0,0 +1,317 @@
+//! Turns delimited buffers like "price,qty,price,qty" into batches of parsed decimals.
+//!
+//! This works like the first stage of simdjson - 16 bytes at a time are compared
+//! against the delimiter, and field boundaries are read off of the resulting bitmask.
+//! The fields are queued up as [`ParseInput`]s and parsed N at a time.
+
+use std::fmt;
+use std::ops::ControlFlow;
+
+use crate::{find_byte_masks, parse_decimals, validate_decimals, ParseInput, ParseOutput};
+
+/// Error from parsing a delimited buffer, with the index of the offending field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanError {
+    /// The field is longer than 16 bytes, so it can't be parsed
+    FieldTooLong { field: usize },
+    /// The field isn't a valid decimal
+    InvalidField { field: usize },
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::FieldTooLong { field } => write!(f, "field {} is too long", field),
+            ScanError::InvalidField { field } => write!(f, "field {} is not a decimal", field),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Parses every field of a buffer separated by delimiter, and appends them to outputs.
+/// Fields are parsed in batches of N, and 8 or 16 are good choices.
+///
+/// Every delimiter separates two fields, so "1,,2" and "1,2," both contain an empty field.
+/// Empty fields parse as zero like they do in [`parse_decimals`], but an empty buffer has no fields.
+/// On error outputs may contain some of the fields, but which ones is unspecified
+///
+/// Examples:
+///
+/// ```
+/// use simd_decimal::scanner::parse_delimited;
+///
+/// let mut outputs = Vec::new();
+/// parse_delimited::<8>(b"27123.45,0.5,27124,1.25", b',', &mut outputs).unwrap();
+///
+/// let mantissas: Vec<u64> = outputs.iter().map(|o| o.mantissa).collect();
+/// assert_eq!(mantissas, [2712345, 5, 27124, 125]);
+/// ```
+pub fn parse_delimited<const N: usize>(
+    buffer: &[u8],
+    delimiter: u8,
+    outputs: &mut Vec<ParseOutput>,
+) -> Result<(), ScanError> {
+    let tail = PaddedTail::new(buffer);
+    let mut batch = BatchParser::<N>::new();
+
+    let scanned = for_each_field(buffer, delimiter, |field, start, end| {
+        let pushed = match ParseInput::new(tail.window(buffer, start), end - start) {
+            Some(input) => batch.push(input, outputs),
+            None => Err(ScanError::FieldTooLong { field }),
+        };
+        break_on_error(pushed)
+    });
+
+    if let ControlFlow::Break(error) = scanned {
+        return Err(error);
+    }
+    batch.finish(outputs)
+}
+
+/// Calls f with (field index, start, end) for every field between delimiters,
+/// and stops at the first one it returns [`ControlFlow::Break`] for
+#[inline]
+pub(crate) fn for_each_field<B>(
+    buffer: &[u8],
+    delimiter: u8,
+    mut f: impl FnMut(usize, usize, usize) -> ControlFlow<B>,
+) -> ControlFlow<B> {
+    if buffer.is_empty() {
+        return ControlFlow::Continue(());
+    }
+
+    let mut field = 0;
+    let mut field_start = 0;
+
+    for_each_chunk(buffer, [delimiter], |chunk_start, [mut delimiters]| {
+        while delimiters != 0 {
+            let end = chunk_start + delimiters.trailing_zeros() as usize;
+            f(field, field_start, end)?;
+            field += 1;
+            field_start = end + 1;
+            delimiters &= delimiters - 1;
+        }
+        ControlFlow::Continue(())
+    })?;
+
+    f(field, field_start, buffer.len())
+}
+
+/// Calls f with the start of every 16 byte chunk, and the masks of where each of bytes are,
+/// and stops at the first chunk it returns [`ControlFlow::Break`] for.
+/// The last chunk is padded, and bits past the end of the buffer are never set
+#[inline]
+pub(crate) fn for_each_chunk<const M: usize, B>(
+    buffer: &[u8],
+    bytes: [u8; M],
+    mut f: impl FnMut(usize, [u32; M]) -> ControlFlow<B>,
+) -> ControlFlow<B> {
+    let mut chunks = buffer.chunks_exact(16);
+    let mut chunk_start = 0;
+
+    for chunk in &mut chunks {
+        let masks = unsafe { find_byte_masks(chunk.try_into().unwrap(), bytes) };
+        f(chunk_start, masks)?;
+        chunk_start += 16;
+    }
+
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        let mut padded = [0; 16];
+        padded[..remainder.len()].copy_from_slice(remainder);
+
+        let in_bounds = (1 << remainder.len()) - 1;
+        let masks = unsafe { find_byte_masks(&padded, bytes) }.map(|mask| mask & in_bounds);
+        f(chunk_start, masks)?;
+    }
+    ControlFlow::Continue(())
+}
+
+/// Breaks out of a scan with the error, if there is one
+#[inline]
+pub(crate) fn break_on_error<E>(result: Result<(), E>) -> ControlFlow<E> {
+    match result {
+        Ok(()) => ControlFlow::Continue(()),
+        Err(error) => ControlFlow::Break(error),
+    }
+}
+
+/// Copy of the last 16 bytes of a buffer with padding after it,
+/// so that every field has 16 loadable bytes even at the very end
+pub(crate) struct PaddedTail {
+    start: usize,
+    bytes: [u8; 32],
+}
+
+impl PaddedTail {
+    pub(crate) fn new(buffer: &[u8]) -> Self {
+        let start = buffer.len().saturating_sub(16);
+        let mut bytes = [0; 32];
+        bytes[..buffer.len() - start].copy_from_slice(&buffer[start..]);
+        PaddedTail { start, bytes }
+    }
+
+    /// The 16 bytes starting at start, which must be at most buffer.len()
+    #[inline]
+    pub(crate) fn window<'a>(&'a self, buffer: &'a [u8], start: usize) -> &'a [u8; 16] {
+        match buffer.get(start..start + 16) {
+            Some(window) => window.try_into().unwrap(),
+            None => self.bytes[start - self.start..][..16].try_into().unwrap(),
+        }
+    }
+}
+
+// Filler for the unused lanes of the last batch
+static PADDING_INPUT: [u8; 16] = [b'0'; 16];
+
+/// Finds the first input that isn't a valid decimal.
+/// This checks them one at a time, so that it works for batches of any size
+#[inline]
+pub(crate) fn first_invalid(inputs: &[ParseInput]) -> Option<usize> {
+    inputs
+        .iter()
+        .position(|input| validate_decimals::<1>(&[*input]) == 0)
+}
+
+/// Collects inputs and parses them N at a time
+pub(crate) struct BatchParser<'a, const N: usize> {
+    pending: [ParseInput<'a>; N],
+    count: usize,
+    parsed: usize,
+}
+
+impl<'a, const N: usize> BatchParser<'a, N> {
+    pub(crate) fn new() -> Self {
+        // push fills the batch before checking if it's full, so it needs room for one
+        const { assert!(N > 0, "a batch has to hold at least one input") };
+        let padding = ParseInput::new(&PADDING_INPUT, 1).unwrap();
+        BatchParser {
+            pending: [padding; N],
+            count: 0,
+            parsed: 0,
+        }
+    }
+
+    /// Queues up an input, and parses the batch if it's full.
+    /// Errors report the index of the input counting from the first one pushed
+    #[inline]
+    pub(crate) fn push(
+        &mut self,
+        input: ParseInput<'a>,
+        outputs: &mut Vec<ParseOutput>,
+    ) -> Result<(), ScanError> {
+        self.pending[self.count] = input;
+        self.count += 1;
+        if self.count == N {
+            self.flush(outputs)?;
+        }
+        Ok(())
+    }
+
+    /// Parses whatever is left over, padding out the batch
+    pub(crate) fn finish(mut self, outputs: &mut Vec<ParseOutput>) -> Result<(), ScanError> {
+        if self.count == 0 {
+            return Ok(());
+        }
+        let padding = ParseInput::new(&PADDING_INPUT, 1).unwrap();
+        self.pending[self.count..].fill(padding);
+        self.flush(outputs)
+    }
+
+    fn flush(&mut self, outputs: &mut Vec<ParseOutput>) -> Result<(), ScanError> {
+        let mut parsed = [ParseOutput::default(); N];
+        if !parse_decimals::<N, false>(&self.pending, &mut parsed) {
+            // Only pay for finding the bad lane when there is one
+            return Err(ScanError::InvalidField {
+                field: self.parsed + first_invalid(&self.pending).unwrap_or(0),
+            });
+        }
+
+        outputs.extend_from_slice(&parsed[..self.count]);
+        self.parsed += self.count;
+        self.count = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mantissas<const N: usize>(buffer: &[u8]) -> Result<Vec<(u64, u8)>, ScanError> {
+        let mut outputs = Vec::new();
+        parse_delimited::<N>(buffer, b',', &mut outputs)?;
+        Ok(outputs.iter().map(|o| (o.mantissa, o.exponent)).collect())
+    }
+
+    #[test]
+    fn test_many_fields() {
+        let expected: Vec<(u64, u8)> = (0..37).map(|i| (i * 1001, 3)).collect();
+        let buffer = expected
+            .iter()
+            .map(|(m, _)| format!("{}.{:03}", m / 1000, m % 1000))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        assert_eq!(mantissas::<8>(buffer.as_bytes()).unwrap(), expected);
+        assert_eq!(mantissas::<16>(buffer.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_empty_fields() {
+        assert_eq!(mantissas::<8>(b"").unwrap(), []);
+        assert_eq!(
+            mantissas::<8>(b"1,,2,").unwrap(),
+            [(1, 0), (0, 0), (2, 0), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            mantissas::<8>(b"1,2,3,4,5,6,7,8,9,1x,2"),
+            Err(ScanError::InvalidField { field: 9 })
+        );
+        assert_eq!(
+            mantissas::<8>(b"1,2,12345678901234567,3"),
+            Err(ScanError::FieldTooLong { field: 2 })
+        );
+    }
+
+    #[test]
+    fn test_errors_in_wide_batches() {
+        let mut buffer = vec!["1"; 70];
+        buffer[45] = "1x";
+        assert_eq!(
+            mantissas::<64>(buffer.join(",").as_bytes()),
+            Err(ScanError::InvalidField { field: 45 })
+        );
+    }
+
+    #[test]
+    fn test_stops_at_break() {
+        let buffer = vec![b','; 40];
+        let mut fields = Vec::new();
+        let scanned = for_each_field(&buffer, b',', |field, _, _| {
+            fields.push(field);
+            if field == 20 {
+                return ControlFlow::Break(field);
+            }
+            ControlFlow::Continue(())
+        });
+
+        assert_eq!(scanned, ControlFlow::Break(20));
+        assert_eq!(fields, (0..=20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_field_at_end_of_long_buffer() {
+        // The last field has less than 16 bytes left in the buffer
+        let buffer = b"1234567890123456,98765.4321";
+        assert_eq!(
+            mantissas::<16>(buffer).unwrap(),
+            [(1234567890123456, 0), (987654321, 4)]
+        );
+    }
+}
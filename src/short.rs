@@ -0,0 +1,95 @@
+//! Parses decimals of at most 8 bytes with SWAR arithmetic on one u64 each.
+//!
+//! A short decimal fits in a general purpose register, so this skips the vector
+//! loads and the length shuffle entirely. The stages mirror the vector parsers,
+//! each as its own loop so the lanes can run in parallel.
+
+use crate::{ParseInput, ParseOutput};
+
+/// The longest input that fits in a u64
+pub(crate) const MAX_SHORT_LENGTH: usize = 8;
+
+const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+// Digits xor '0' are 0..9 in every byte, and the dot becomes this
+const CLEANED_DOT: u64 = (b'.' ^ b'0') as u64 * LOW_BITS;
+
+// Adding this to a cleaned byte sets its high bit if it's larger than 9
+const ABOVE_NINE: u64 = (0x80 - 10) * LOW_BITS;
+
+/// Parses the inputs like [`crate::parse_decimals`], and returns false
+/// if any are invalid or longer than [`MAX_SHORT_LENGTH`]
+#[inline(always)]
+pub(crate) fn do_parse_short_decimals<const N: usize, const KNOWN_INTEGER: bool>(
+    inputs: &[ParseInput; N],
+    outputs: &mut [ParseOutput; N],
+) -> bool {
+    let mut lengths_good = true;
+    let mut cleaned = [0; N];
+    for i in 0..N {
+        let length = inputs[i].real_length.get();
+        lengths_good &= length <= MAX_SHORT_LENGTH;
+        cleaned[i] = load_and_shift(inputs[i].data, length);
+    }
+
+    if !KNOWN_INTEGER {
+        for i in 0..N {
+            outputs[i].exponent = remove_dot(&mut cleaned[i]);
+        }
+    }
+
+    let mut all_good = lengths_good;
+    for digits in cleaned.iter() {
+        all_good &= (digits.wrapping_add(ABOVE_NINE) | digits) & HIGH_BITS == 0;
+    }
+
+    for i in 0..N {
+        outputs[i].mantissa = reduce_to_mantissa(cleaned[i]);
+    }
+
+    all_good
+}
+
+/// Loads the first 8 bytes, cleans them so digits become 0..9, and shifts the decimal
+/// to the most significant end. The bytes shifted in are zero, which are leading zeros
+#[inline(always)]
+fn load_and_shift(data: &[u8; 16], length: usize) -> u64 {
+    let mut bytes = [0; MAX_SHORT_LENGTH];
+    bytes.copy_from_slice(&data[..MAX_SHORT_LENGTH]);
+    let cleaned = u64::from_le_bytes(bytes) ^ (b'0' as u64 * LOW_BITS);
+
+    // A length above 8 is already rejected, so it doesn't matter what it shifts to
+    let shift = (MAX_SHORT_LENGTH.saturating_sub(length) * 8) as u32;
+    cleaned.checked_shl(shift).unwrap_or(0)
+}
+
+/// Removes the first dot by moving every digit before it up a byte, and returns how many
+/// digits are after it. A second dot is left in place to fail the digit check
+#[inline(always)]
+fn remove_dot(cleaned: &mut u64) -> u8 {
+    // The usual zero byte test, whose lowest set bit is always exact
+    let is_dot = *cleaned ^ CLEANED_DOT;
+    let dot_bits = is_dot.wrapping_sub(LOW_BITS) & !is_dot & HIGH_BITS;
+    if dot_bits == 0 {
+        return 0;
+    }
+
+    let dot_idx = dot_bits.trailing_zeros() / 8;
+    let before = (1u64 << (dot_idx * 8)) - 1;
+    let after = !before << 8;
+    *cleaned = ((*cleaned & before) << 8) | (*cleaned & after);
+
+    (MAX_SHORT_LENGTH as u32 - 1 - dot_idx) as u8
+}
+
+/// Combines the 8 digits with the most significant in the lowest byte, by multiplying
+/// adjacent pairs together the same way the vector reduction does
+#[inline(always)]
+fn reduce_to_mantissa(digits: u64) -> u64 {
+    // Each multiply adds the more significant half, scaled up, onto the less significant half.
+    // Invalid bytes can overflow, but the result is thrown away for those anyways
+    let pairs = (digits.wrapping_mul(1 + (10 << 8)) >> 8) & 0x00ff_00ff_00ff_00ff;
+    let quads = (pairs.wrapping_mul(1 + (100 << 16)) >> 16) & 0x0000_ffff_0000_ffff;
+    quads.wrapping_mul(1 + (10000 << 32)) >> 32
+}
@@ -0,0 +1,29 @@
+//! Scalar helpers for the strict JSON number grammar.
+//! Like the grouping checks, these work on comparison bitmasks of the right-aligned number
+
+use crate::DecimalLen;
+
+/// Returns true if the number matches the RFC 8259 grammar, ignoring the parts this crate
+/// never accepts anyway (signs and exponents). That means it is non-empty,
+/// has at least one digit on both sides of the dot,
+/// and only has a leading zero if the zero is the whole integer part.
+/// zero_mask has bit i set if byte i of the vector is '0', and dot_idx is 16 without a dot
+#[inline(always)]
+pub(crate) fn strict_grammar_is_valid(
+    zero_mask: u32,
+    dot_idx: u32,
+    real_length: DecimalLen,
+) -> bool {
+    let start = 16 - real_length.get() as u32;
+
+    // The padding in front of the number is zeroed, so this also catches the empty string
+    let integer_length = dot_idx.wrapping_sub(start);
+    let has_integer = (1..=16).contains(&integer_length);
+
+    let dot_not_at_end = dot_idx != 15;
+
+    let leading_zero = (zero_mask >> start) & 1 == 1;
+    let zero_is_alone = !leading_zero || integer_length == 1;
+
+    has_integer & dot_not_at_end & zero_is_alone
+}
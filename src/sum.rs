@@ -0,0 +1,77 @@
+//! Scalar helpers for lining decimals up at a fixed scale, for sums and sort keys.
+//!
+//! The backends line up the digits of each lane at a common scale before reducing them,
+//! so that the mantissas can be used directly. Lanes with too many digits to be shifted
+//! inside of the vector are rescaled here instead
+
+use crate::ParseOutput;
+
+/// The most decimals a value in a single vector can have
+pub(crate) const MAX_VECTOR_SCALE: u8 = 15;
+
+/// How many lanes to shift a lane by to move it from exponent to vector_scale,
+/// or None if it has too many digits for that.
+/// zero_mask has bit i set if lane i is a zero digit
+#[inline(always)]
+pub(crate) fn scale_shift(zero_mask: u32, exponent: u8, vector_scale: u8) -> Option<u8> {
+    let shift = vector_scale.checked_sub(exponent)?;
+    // Only leading zeros can be shifted out of the top of the vector
+    let leading_zeros = (!zero_mask).trailing_zeros();
+    (shift as u32 <= leading_zeros).then_some(shift)
+}
+
+/// Adds up lanes which are already at vector_scale, and rescales the others one at a time.
+/// Returns None if any lane has more decimals than scale, or the sum overflows
+#[inline(always)]
+pub(crate) fn sum_at_scale<const N: usize>(
+    parsed: &[ParseOutput; N],
+    aligned: &[bool; N],
+    vector_scale: u8,
+    scale: u8,
+) -> Option<i128> {
+    let mut aligned_sum = 0u128;
+    let mut unaligned_sum = 0i128;
+
+    for i in 0..N {
+        if aligned[i] {
+            aligned_sum += parsed[i].mantissa as u128;
+        } else {
+            let shift = scale.checked_sub(parsed[i].exponent)?;
+            let rescaled = (parsed[i].mantissa as i128).checked_mul(pow10(shift)?)?;
+            unaligned_sum = unaligned_sum.checked_add(rescaled)?;
+        }
+    }
+
+    let aligned_sum = i128::try_from(aligned_sum).ok()?;
+    aligned_sum
+        .checked_mul(pow10(scale - vector_scale)?)?
+        .checked_add(unaligned_sum)
+}
+
+/// The mantissa of a lane at scale decimals, or None if it has more decimals than that
+/// or doesn't fit in a u64
+#[inline(always)]
+pub(crate) fn mantissa_at_scale(
+    parsed: ParseOutput,
+    aligned: bool,
+    vector_scale: u8,
+    scale: u8,
+) -> Option<u64> {
+    let exponent = if aligned {
+        vector_scale
+    } else {
+        parsed.exponent
+    };
+    let shift = scale.checked_sub(exponent)?;
+    if parsed.mantissa == 0 {
+        return Some(0);
+    }
+    parsed
+        .mantissa
+        .checked_mul(10u64.checked_pow(shift as u32)?)
+}
+
+#[inline(always)]
+fn pow10(exponent: u8) -> Option<i128> {
+    10i128.checked_pow(exponent as u32)
+}
@@ -24,6 +24,28 @@ pub(crate) const LENGTH_SHIFT_CONTROL: VecCharArray<17> = VecCharArray {
     chars: generate_length_shift_control(),
 };
 
+// Indexed by 16 * (number of separators) + (index of the first separator)
+pub(crate) const GROUP_SHUFFLE_CONTROL: VecCharArray<64> = VecCharArray {
+    chars: generate_group_shuffle_control(),
+};
+
+// Indexed by the length of a packed decimal in bytes. Every byte is duplicated into two lanes
+// so that the nibbles can be split apart, with the field right-aligned into all 16 lanes
+pub(crate) const PACKED_SHUFFLE_CONTROL: VecCharArray<9> = VecCharArray {
+    chars: generate_packed_shuffle_control(),
+};
+
+// Indexed by how many places to shift a right-aligned decimal towards the most significant lane,
+// which multiplies it by that power of ten
+pub(crate) const SCALE_SHIFT_CONTROL: VecCharArray<16> = VecCharArray {
+    chars: generate_scale_shift_control(),
+};
+
+// Indexed by the length of an input of at most 4 bytes, as the 4 shuffle control bytes that
+// right-align it within its own u32. Zero fill is 0x80 rather than 0xff, so that adding the offset
+// of the u32 to every byte keeps the high bit set without carrying into the next byte
+pub(crate) const SMALL_SHIFT_CONTROL: [u32; 5] = generate_small_shift_control();
+
 pub(crate) const EXPONENT_FROM_BITS: [u8; 17] =
     [15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, 0];
 
@@ -140,3 +162,91 @@ const fn generate_length_shift_control() -> [[u8; 16]; 17] {
         generate_length_shift_for(16),
     ]
 }
+
+const fn generate_group_removal(count: u8, lowest: u8) -> [u8; 16] {
+    // Same idea as the dot removal, except there are up to three bytes removed
+    // every fourth index starting from lowest. We walk down from the end,
+    // skipping separators, and zero fill whatever is left at the front
+    let mut control = [u8::MAX; 16];
+    let mut src = 16;
+    let mut dst = 16;
+    while src > 0 {
+        src -= 1;
+        let is_separator = src >= lowest && (src - lowest) & 3 == 0 && (src - lowest) / 4 < count;
+        if !is_separator {
+            dst -= 1;
+            control[dst] = src;
+        }
+    }
+    control
+}
+
+const fn generate_group_shuffle_control() -> [[u8; 16]; 64] {
+    let mut controls = [[0; 16]; 64];
+    let mut i = 0;
+    while i < 64 {
+        controls[i] = generate_group_removal((i / 16) as u8, (i % 16) as u8);
+        i += 1;
+    }
+    controls
+}
+
+const fn generate_packed_for(length: u8) -> [u8; 16] {
+    let mut control = [u8::MAX; 16];
+    let padding = 8 - length;
+    let mut i = 0;
+    while i < 16 {
+        let byte = i / 2;
+        if byte >= padding {
+            control[i as usize] = byte - padding;
+        }
+        i += 1;
+    }
+    control
+}
+
+const fn generate_packed_shuffle_control() -> [[u8; 16]; 9] {
+    let mut controls = [[0; 16]; 9];
+    let mut i = 0;
+    while i < 9 {
+        controls[i] = generate_packed_for(i as u8);
+        i += 1;
+    }
+    controls
+}
+
+const fn generate_scale_shift_for(shift: u8) -> [u8; 16] {
+    let mut control = [u8::MAX; 16];
+    let mut i = 0;
+    while i + shift < 16 {
+        control[i as usize] = i + shift;
+        i += 1;
+    }
+    control
+}
+
+const fn generate_scale_shift_control() -> [[u8; 16]; 16] {
+    let mut controls = [[0; 16]; 16];
+    let mut i = 0;
+    while i < 16 {
+        controls[i] = generate_scale_shift_for(i as u8);
+        i += 1;
+    }
+    controls
+}
+
+const fn generate_small_shift_control() -> [u32; 5] {
+    let mut controls = [0; 5];
+    let mut length = 0;
+    while length < 5 {
+        let mut control = [0x80; 4];
+        let mut i = 4 - length;
+        while i < 4 {
+            control[i] = (i - (4 - length)) as u8;
+            i += 1;
+        }
+        controls[length] = u32::from_le_bytes(control);
+        length += 1;
+    }
+    controls
+}